@@ -0,0 +1,70 @@
+// Evolves a substrate for the visual discrimination task and checks how the
+// champion generalizes to larger retinas.
+//
+//     cargo run --release --example boxes [generations]
+
+use hyper_darwin::tasks::{Boxes, Task};
+use hyper_darwin::{seed, HyperSettings, Population, Settings};
+
+use std::env;
+use std::time::Instant;
+
+fn main() {
+    let generations = env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(100);
+
+    seed(11);
+
+    let task = Boxes::default();
+    let hyper = HyperSettings::default();
+    let mut pop = Population::new(Settings::new(100).third_param(true));
+
+    let start = Instant::now();
+    let mut evaluated = 0;
+
+    for gen in 0..generations {
+        evaluated += 1;
+
+        for genome in pop.get_citizens().iter_mut() {
+            genome.fitness = 0.;
+            genome.add_fitness(task.evaluate(genome, &hyper));
+        }
+
+        let best = pop
+            .citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+            .unwrap();
+
+        println!("gen {:>4}  best {:.4}", gen, best.fitness);
+
+        if task.solved(best, &hyper) {
+            break;
+        }
+
+        pop.next_generation();
+    }
+
+    println!("{:.2?} per generation", start.elapsed() / evaluated.max(1));
+
+    let champion = pop
+        .citizens()
+        .iter()
+        .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+        .unwrap();
+
+    for &resolution in &[11, 22, 33] {
+        let report = task
+            .clone()
+            .resolution(resolution)
+            .report(champion, &hyper)
+            .unwrap();
+
+        println!(
+            "{0}x{0}: fitness {1:.4}  mean distance {2:.4}  accuracy {3:.2}",
+            resolution, report.fitness, report.mean_distance, report.accuracy
+        );
+    }
+}
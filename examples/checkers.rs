@@ -0,0 +1,64 @@
+// Evolves a HyperNEAT checkers board evaluator against the material counting
+// opponent, then replays the champion's games.
+//
+//     cargo run --release --example checkers
+
+use hyper_darwin::tasks::{Checkers, Outcome, Side, Task};
+use hyper_darwin::{seed, HyperSettings, Population, Settings, Speciation};
+
+fn main() {
+    seed(1);
+
+    let task = Checkers::default();
+    let hyper = HyperSettings::default();
+
+    let sets = Settings::new(100)
+        .node_mut_rate(0.05)
+        .conn_mut_rate(0.1)
+        .speciation(Speciation::KMedoids(8))
+        .global_elitism(2);
+
+    let mut pop = Population::new(sets);
+
+    for gen in 0..100 {
+        for genome in pop.get_citizens().iter_mut() {
+            genome.fitness = 0.;
+            genome.add_fitness(task.evaluate(genome, &hyper));
+        }
+
+        let best = pop
+            .citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+            .unwrap();
+
+        println!("generation {:3}  best {:.4}", gen, best.fitness);
+
+        if task.solved(best, &hyper) {
+            let network = task.network(best, &hyper).unwrap();
+
+            for (game, side) in task.games(&network).iter().zip(&[Side::Black, Side::White]) {
+                let result = match game.outcome {
+                    Outcome::Win(winner) if winner == *side => "won",
+                    Outcome::Win(_) => "lost",
+                    Outcome::Draw => "drew",
+                };
+
+                println!(
+                    "as {:?}: {} in {} plies, material {} to {}",
+                    side,
+                    result,
+                    game.plies,
+                    game.board.material(*side),
+                    game.board.material(side.opponent())
+                );
+            }
+
+            return;
+        }
+
+        pop.next_generation();
+    }
+
+    println!("not solved");
+}
@@ -0,0 +1,85 @@
+// Evolves a controller for a custom, noisy Environment: a point on a line has
+// to reach a target that starts somewhere random, with jittery movements.
+// Averaging over several trials keeps lucky Genomes from taking over.
+//
+//     cargo run --release --example environment
+
+use hyper_darwin::{seed, Environment, EpisodeRunner, Population, Settings, Space, Step};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+struct Seek {
+    position: f64,
+    target: f64,
+    rng: StdRng,
+}
+
+impl Seek {
+    fn distance(&self) -> f64 {
+        (self.target - self.position).abs()
+    }
+}
+
+impl Environment for Seek {
+    fn observation_space(&self) -> Space {
+        Space::uniform(2, -1., 1.)
+    }
+
+    fn action_space(&self) -> Space {
+        Space::uniform(1, -1., 1.)
+    }
+
+    fn reset(&mut self) -> Vec<f64> {
+        self.position = 0.;
+        self.target = self.rng.gen_range(-1., 1.);
+
+        vec![self.position, self.target]
+    }
+
+    // Rewards progress towards the target, plus 1 for reaching it
+    fn step(&mut self, action: &[f64]) -> Step {
+        let before = self.distance();
+        let jitter = self.rng.gen_range(-0.02, 0.02);
+
+        self.position = (self.position + 0.1 * action[0] + jitter).clamp(-1., 1.);
+
+        let done = self.distance() < 0.05;
+
+        Step {
+            observation: vec![self.position, self.target],
+            reward: before - self.distance() + if done { 1. } else { 0. },
+            done,
+        }
+    }
+}
+
+fn main() {
+    seed(1);
+
+    let mut env = Seek {
+        position: 0.,
+        target: 0.,
+        rng: StdRng::seed_from_u64(1),
+    };
+
+    let runner = EpisodeRunner::new().episodes(4).trials(3).max_steps(50);
+    let mut pop = Population::new(Settings::new(100).direct(2, 1));
+
+    for gen in 0..30 {
+        for genome in pop.get_citizens().iter_mut() {
+            let fitness = runner.run(&mut env, &*genome).unwrap_or(0.);
+
+            genome.fitness = 0.;
+            genome.add_fitness(fitness);
+        }
+
+        let best = pop.citizens().iter().map(|g| g.fitness).fold(0., f64::max);
+
+        if gen % 5 == 0 {
+            println!("gen {:>3}  best {:.3}", gen, best);
+        }
+
+        pop.next_generation();
+    }
+}
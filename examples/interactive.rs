@@ -0,0 +1,107 @@
+// Picbreeder in the terminal: every generation of color CPPNs is drawn to
+// PNG files, and you pick the ones to breed from.
+//
+//     cargo run --release --example interactive [output dir]
+//
+// Then at the prompt:
+//
+//     3 7 12           breed the next generation from images 3, 7 and 12
+//     save 7 a.json    save the Genome behind image 7
+//     branch a.json    start over from a saved Genome
+//     quit
+
+use hyper_darwin::{
+    load_genome, save_genome, ActivationSet, HyperDarwinError, InteractiveEvolution, Renderer,
+    Settings,
+};
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+fn draw(session: &InteractiveEvolution, renderer: &Renderer, dir: &Path) {
+    for (i, genome) in session.genomes().iter().enumerate() {
+        renderer
+            .render(genome)
+            .and_then(|image| image.save(dir.join(format!("{:02}.png", i))))
+            .unwrap();
+    }
+
+    println!(
+        "generation {} drawn to {}/00.png to {:02}.png",
+        session.generations,
+        dir.display(),
+        session.genomes().len() - 1
+    );
+}
+
+fn command(session: &mut InteractiveEvolution, line: &str) -> Result<(), HyperDarwinError> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+
+    match words.as_slice() {
+        ["save", index, path] => {
+            let index = index.parse().unwrap_or(usize::MAX);
+            let genome = session
+                .genome(index)
+                .ok_or(HyperDarwinError::SelectionOutOfRange {
+                    index,
+                    len: session.genomes().len(),
+                })?;
+
+            save_genome(genome, path)
+        }
+        ["branch", path] => session.branch(&load_genome(path)?),
+        indices => {
+            let indices = indices
+                .iter()
+                .filter_map(|i| i.parse().ok())
+                .collect::<Vec<usize>>();
+
+            session.select_only(&indices)?;
+            session.next_generation()
+        }
+    }
+}
+
+fn main() {
+    let dir = PathBuf::from(
+        env::args()
+            .nth(1)
+            .unwrap_or_else(|| String::from("picbreeder")),
+    );
+    fs::create_dir_all(&dir).unwrap();
+
+    // x, y, distance from the center and bias in, red, green and blue out
+    let renderer = Renderer::new(128, 128).distance(true).bias(true);
+
+    let sets = Settings::new(15)
+        .direct(4, 3)
+        .activations(ActivationSet::Cppn)
+        .node_mut_rate(0.3)
+        .conn_mut_rate(0.5);
+
+    let mut session = InteractiveEvolution::new(sets);
+    draw(&session, &renderer, &dir);
+
+    let stdin = io::stdin();
+
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+
+        if stdin.lock().read_line(&mut line).unwrap() == 0 || line.trim() == "quit" {
+            break;
+        }
+
+        let generation = session.generations;
+
+        match command(&mut session, &line) {
+            Ok(()) if session.generations != generation => draw(&session, &renderer, &dir),
+            Ok(()) => {}
+            Err(err) => println!("{}", err),
+        }
+    }
+}
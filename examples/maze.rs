@@ -0,0 +1,95 @@
+// Compares objective fitness with novelty search on a deceptive maze, where
+// the final position of the robot is the behavior descriptor. Each mode gets
+// the same seeds and reports the generation the goal was first reached.
+//
+//     cargo run --release --example maze [medium|hard|<maze file>] [runs]
+
+use hyper_darwin::tasks::{Maze, MazeNavigation, Task};
+use hyper_darwin::{seed, FitnessMode, HyperSettings, Population, Settings};
+
+use std::env;
+
+const GENERATIONS: u64 = 400;
+
+fn run(task: &MazeNavigation, mode: FitnessMode, run_seed: u64) -> (Option<u64>, f64) {
+    seed(run_seed);
+
+    let hyper = HyperSettings::default();
+
+    let sets = Settings::new(250)
+        .direct(10, 2)
+        .node_mut_rate(0.05)
+        .conn_mut_rate(0.1)
+        .speciation_threshold(1.)
+        .fitness_mode(mode)
+        .novelty_k(15)
+        .novelty_threshold(6.);
+
+    let mut pop = Population::new(sets);
+    let mut closest = f64::INFINITY;
+
+    for gen in 0..GENERATIONS {
+        let mut solved = false;
+
+        for genome in pop.get_citizens().iter_mut() {
+            let navigator = task.run(genome).unwrap();
+
+            genome.fitness = 0.;
+            genome.add_fitness(navigator.fitness());
+            genome.set_behavior(vec![navigator.position.0, navigator.position.1]);
+
+            closest = closest.min(navigator.distance_to_goal());
+            solved |= task.solved(genome, &hyper);
+        }
+
+        if solved {
+            return (Some(gen), closest);
+        }
+
+        pop.next_generation();
+    }
+
+    (None, closest)
+}
+
+fn main() {
+    let maze = match env::args().nth(1) {
+        Some(name) => Maze::named(&name).expect("couldn't load the maze"),
+        None => Maze::medium(),
+    };
+
+    let runs = env::args()
+        .nth(2)
+        .and_then(|runs| runs.parse().ok())
+        .unwrap_or(5);
+
+    let task = MazeNavigation {
+        maze,
+        ..MazeNavigation::default()
+    };
+
+    for &(name, mode) in &[
+        ("objective", FitnessMode::Objective),
+        ("novelty", FitnessMode::Novelty),
+    ] {
+        let mut solved = 0;
+
+        for run_seed in 1..=runs {
+            match run(&task, mode, run_seed) {
+                (Some(gen), _) => {
+                    solved += 1;
+                    println!(
+                        "{} run {}: goal reached in generation {}",
+                        name, run_seed, gen
+                    );
+                }
+                (None, closest) => println!(
+                    "{} run {}: not solved, closest {:.1} from the goal",
+                    name, run_seed, closest
+                ),
+            }
+        }
+
+        println!("{}: {} of {} runs solved\n", name, solved, runs);
+    }
+}
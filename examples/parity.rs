@@ -0,0 +1,61 @@
+// Evolves a plain NEAT network for N-bit parity.
+//
+//     cargo run --release --example parity [bits]
+
+use hyper_darwin::tasks::{Parity, Task};
+use hyper_darwin::{
+    seed, ActivationSet, Activations, HyperSettings, Population, Settings, Speciation,
+};
+
+use std::env;
+
+fn main() {
+    let bits = env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(3);
+
+    seed(1);
+
+    let task = Parity::new(bits).unwrap();
+    let hyper = HyperSettings::default();
+
+    let sets = Settings::new(150)
+        .direct(bits, 1)
+        .activations(ActivationSet::Single(Activations::Sigmoid))
+        .node_mut_rate(0.1)
+        .conn_mut_rate(0.2)
+        .speciation(Speciation::KMedoids(12))
+        .allowed_stagnancy(30);
+
+    let mut pop = Population::new(sets);
+
+    for gen in 0..1000 {
+        for genome in pop.get_citizens().iter_mut() {
+            genome.fitness = 0.;
+            genome.add_fitness(task.evaluate(genome, &hyper));
+        }
+
+        let best = pop
+            .citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+            .unwrap();
+
+        if task.solved(best, &hyper) {
+            println!("solved {}-bit parity in generation {}", bits, gen);
+
+            println!(
+                "{} nodes, {} connections",
+                best.nodes.len(),
+                best.conns.iter().filter(|c| c.enabled).count()
+            );
+
+            return;
+        }
+
+        pop.next_generation();
+    }
+
+    println!("not solved");
+}
@@ -0,0 +1,69 @@
+// Evolves a plain NEAT controller for Markovian pole balancing, then puts the
+// champion through the 100,000 step and generalization tests.
+//
+//     cargo run --release --example pole_balancing [single|double]
+
+use hyper_darwin::tasks::{PoleBalancing, Poles, Task};
+use hyper_darwin::{seed, HyperSettings, Population, Settings};
+
+use std::env;
+
+fn main() {
+    let poles = match env::args().nth(1).as_deref() {
+        Some("single") => Poles::Single,
+        _ => Poles::Double,
+    };
+
+    seed(1);
+
+    let task = PoleBalancing {
+        poles,
+        ..PoleBalancing::default()
+    };
+
+    let hyper = HyperSettings::default();
+    let inputs = task.cart().observation_size() as u32;
+
+    let sets = Settings::new(150)
+        .direct(inputs, 1)
+        .node_mut_rate(0.05)
+        .conn_mut_rate(0.1)
+        .speciation_threshold(1.);
+
+    let mut pop = Population::new(sets);
+
+    for gen in 0..500 {
+        for genome in pop.get_citizens().iter_mut() {
+            genome.fitness = 0.;
+            genome.add_fitness(task.evaluate(genome, &hyper));
+        }
+
+        let best = pop
+            .citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+            .unwrap();
+
+        if task.solved(best, &hyper) {
+            println!("{:?} poles balanced in generation {}", poles, gen);
+
+            let controller = || move |obs: &[f64]| best.feed_forward(obs).unwrap();
+            let mut cart = task.cart();
+
+            println!(
+                "100,000 step test passed: {}",
+                cart.long_run_test(controller())
+            );
+            println!(
+                "generalization: {} of 625 starts",
+                cart.generalization_test(controller)
+            );
+
+            return;
+        }
+
+        pop.next_generation();
+    }
+
+    println!("not solved");
+}
@@ -0,0 +1,73 @@
+// Draws the patterns of a few randomly mutated CPPNs, in grayscale and color,
+// and the connectivity of a substrate from its center point.
+//
+//     cargo run --release --example render [output dir]
+
+use hyper_darwin::{
+    render_connectivity, seed, Genome, History, HyperSettings, Renderer, Settings, Substrate,
+};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn mutated(inputs: u32, outputs: u32, mutations: usize, sets: &Settings) -> Genome {
+    let mut hist = History::new(inputs, outputs);
+    let mut genome = Genome::new(inputs, outputs, false);
+
+    for _ in 0..mutations {
+        genome.mutate(&mut hist, sets);
+    }
+
+    genome
+}
+
+fn main() {
+    let dir = PathBuf::from(
+        env::args()
+            .nth(1)
+            .unwrap_or_else(|| String::from("patterns")),
+    );
+    fs::create_dir_all(&dir).unwrap();
+
+    seed(3);
+
+    let sets = Settings::new(1).node_mut_rate(0.5).conn_mut_rate(0.5);
+
+    // x, y, distance from the center and bias
+    let renderer = Renderer::new(256, 256).distance(true).bias(true);
+
+    for i in 0..4 {
+        let gray = mutated(4, 1, 30, &sets);
+        renderer
+            .render(&gray)
+            .unwrap()
+            .save(dir.join(format!("gray_{}.png", i)))
+            .unwrap();
+
+        let color = mutated(4, 3, 30, &sets);
+        renderer
+            .render(&color)
+            .unwrap()
+            .save(dir.join(format!("color_{}.png", i)))
+            .unwrap();
+    }
+
+    let cppn = mutated(4, 1, 30, &sets);
+    let substrate = Substrate::decode(
+        &cppn,
+        11,
+        11,
+        &HyperSettings::default(),
+        None::<fn(f64, f64) -> f64>,
+    )
+    .unwrap();
+
+    render_connectivity(&substrate, (5, 5))
+        .unwrap()
+        .scaled(16)
+        .save(dir.join("connectivity.png"))
+        .unwrap();
+
+    println!("patterns written to {}", dir.display());
+}
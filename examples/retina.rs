@@ -0,0 +1,63 @@
+// Evolves a layered HyperNEAT substrate for the Left & Right Retina problem.
+//
+//     cargo run --release --example retina
+
+use hyper_darwin::tasks::{Retina, Task};
+use hyper_darwin::{seed, HyperSettings, Population, Settings, Speciation};
+
+fn main() {
+    seed(1);
+
+    let task = Retina::default();
+    let hyper = HyperSettings::default();
+
+    let sets = Settings::new(150)
+        .node_mut_rate(0.05)
+        .conn_mut_rate(0.1)
+        .speciation(Speciation::KMedoids(10))
+        .allowed_stagnancy(30)
+        .global_elitism(2);
+
+    let mut pop = Population::new(sets);
+
+    for gen in 0..1000 {
+        for genome in pop.get_citizens().iter_mut() {
+            genome.fitness = 0.;
+            genome.add_fitness(task.evaluate(genome, &hyper));
+        }
+
+        let best = pop
+            .citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+            .unwrap();
+
+        if task.solved(best, &hyper) {
+            println!("solved in generation {}", gen);
+
+            let network = task.network(best, &hyper).unwrap();
+
+            for (i, layer) in network.layers().iter().enumerate().skip(1) {
+                for (j, &(x, y)) in layer.iter().enumerate() {
+                    let weights = (0..network.layers()[i - 1].len())
+                        .map(|k| format!("{:>6.2}", network.weight(i - 1, k, j)))
+                        .collect::<Vec<String>>();
+
+                    println!(
+                        "({:>5.2}, {:>5.2})  bias {:>6.2}  weights {}",
+                        x,
+                        y,
+                        network.bias(i, j),
+                        weights.join(" ")
+                    );
+                }
+            }
+
+            return;
+        }
+
+        pop.next_generation();
+    }
+
+    println!("not solved");
+}
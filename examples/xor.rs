@@ -0,0 +1,58 @@
+// Evolves a plain NEAT network for XOR.
+//
+//     cargo run --release --example xor
+
+use hyper_darwin::tasks::{Task, Xor};
+use hyper_darwin::{seed, ActivationSet, Activations, HyperSettings, Population, Settings};
+
+fn main() {
+    seed(1);
+
+    let task = Xor::default();
+    let hyper = HyperSettings::default();
+
+    let sets = Settings::new(150)
+        .direct(2, 1)
+        .activations(ActivationSet::Single(Activations::Sigmoid))
+        .node_mut_rate(0.1)
+        .conn_mut_rate(0.2)
+        .speciation_threshold(1.);
+
+    let mut pop = Population::new(sets);
+
+    for gen in 0..300 {
+        for genome in pop.get_citizens().iter_mut() {
+            genome.fitness = 0.;
+            genome.add_fitness(task.evaluate(genome, &hyper));
+        }
+
+        let best = pop
+            .citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+            .unwrap();
+
+        if task.solved(best, &hyper) {
+            println!("solved in generation {}", gen);
+
+            for a in 0..2 {
+                for b in 0..2 {
+                    let out = best.feed_forward(&[a as f64, b as f64]).unwrap();
+                    println!("{} xor {} = {:.3}", a, b, (out + 1.) / 2.);
+                }
+            }
+
+            println!(
+                "{} nodes, {} connections",
+                best.nodes.len(),
+                best.conns.iter().filter(|c| c.enabled).count()
+            );
+
+            return;
+        }
+
+        pop.next_generation();
+    }
+
+    println!("not solved");
+}
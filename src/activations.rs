@@ -1,8 +1,10 @@
 use rand::distributions::{Distribution, Standard};
+use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp::PartialEq;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Activations {
     Sine,
     Cosine,
@@ -25,3 +27,36 @@ impl Distribution<Activations> for Standard {
         }
     }
 }
+
+impl Activations {
+    pub fn apply(&self, val: f64) -> f64 {
+        match self {
+            Activations::Linear => val,
+            Activations::Absolute => val.abs(),
+            Activations::Sigmoid => 1. / (1. + (-4.9 * val).exp()),
+            Activations::Sine => val.sin(),
+            Activations::Cosine => val.cos(),
+            Activations::Gaussian => (-(val.powi(2) / 2.)).exp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ActivationSet {
+    Cppn,                     // Every activation, picked at random
+    Single(Activations),      // Plain NEAT with one activation everywhere
+    Custom(Vec<Activations>), // Picked at random from the given ones
+}
+
+impl ActivationSet {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Activations {
+        match self {
+            ActivationSet::Cppn => rng.gen::<Activations>(),
+            ActivationSet::Single(activation) => *activation,
+            ActivationSet::Custom(activations) => match activations.choose(rng) {
+                Some(activation) => *activation,
+                None => rng.gen::<Activations>(),
+            },
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::history::History;
+use crate::population::Population;
+use crate::settings::Settings;
+
+use std::mem;
+use std::vec::Vec;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Topology {
+    Ring,
+    FullyConnected,
+}
+
+// Several Populations evolving side by side, occasionally exchanging their best
+// Genomes. All islands share a single innovation History so that the genes of
+// migrants line up with the genes of the locals during crossover.
+pub struct Archipelago {
+    islands: Vec<Population>,
+    hist: History,
+    pub topology: Topology,
+    pub migration_interval: u64, // Generations between migrations, 0 never migrates
+    pub migrants: usize,
+    pub generations: u64,
+}
+
+impl Archipelago {
+    pub fn new(settings: Vec<Settings>) -> Result<Self, HyperDarwinError> {
+        if settings.is_empty() {
+            return Err(HyperDarwinError::NoIslands);
+        }
+
+        let inputs = settings[0].genome_inputs();
+        let outputs = settings[0].genome_outputs();
+
+        for sets in &settings {
+            sets.validate()?;
+
+            let actual = (sets.genome_inputs(), sets.genome_outputs());
+
+            if actual != (inputs, outputs) {
+                return Err(HyperDarwinError::IncompatibleIslands {
+                    expected: (inputs, outputs),
+                    actual,
+                });
+            }
+        }
+
+        Ok(Self {
+            islands: settings.into_iter().map(Population::new).collect(),
+            hist: History::new(inputs, outputs),
+            topology: Topology::Ring,
+            migration_interval: 10,
+            migrants: 2,
+            generations: 0,
+        })
+    }
+
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn migration_interval(mut self, interval: u64) -> Self {
+        self.migration_interval = interval;
+        self
+    }
+
+    pub fn migrants(mut self, migrants: usize) -> Self {
+        self.migrants = migrants;
+        self
+    }
+
+    pub fn islands(&self) -> &[Population] {
+        &self.islands
+    }
+
+    pub fn islands_mut(&mut self) -> &mut [Population] {
+        &mut self.islands
+    }
+
+    pub fn best_genome(&self) -> Option<&Genome> {
+        self.islands
+            .iter()
+            .filter_map(|island| island.best_genome.as_ref())
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+    }
+
+    // Expects every island to have been evaluated. Migrates if it is time to,
+    // then advances every island by one generation.
+    pub fn next_generation(&mut self) {
+        let interval = self.migration_interval;
+
+        if interval > 0 && self.generations > 0 && self.generations % interval == 0 {
+            self.migrate();
+        }
+
+        for island in self.islands.iter_mut() {
+            mem::swap(&mut island.hist, &mut self.hist);
+            island.next_generation();
+            mem::swap(&mut island.hist, &mut self.hist);
+        }
+
+        self.generations += 1;
+    }
+
+    fn sources(&self, dest: usize) -> Vec<usize> {
+        let len = self.islands.len();
+
+        match self.topology {
+            Topology::Ring => {
+                if len < 2 {
+                    Vec::new()
+                } else {
+                    vec![(dest + len - 1) % len]
+                }
+            }
+            Topology::FullyConnected => (0..len).filter(|&i| i != dest).collect(),
+        }
+    }
+
+    // Sends copies of the best `migrants` Genomes of every island to its
+    // neighbours, where they replace the worst locals
+    pub fn migrate(&mut self) {
+        let emigrants = self
+            .islands
+            .iter()
+            .map(|island| island.top_genomes(self.migrants))
+            .collect::<Vec<Vec<Genome>>>();
+
+        for dest in 0..self.islands.len() {
+            let migrants = self
+                .sources(dest)
+                .into_iter()
+                .flat_map(|src| emigrants[src].iter().cloned())
+                .collect::<Vec<Genome>>();
+
+            self.islands[dest].immigrate(migrants);
+        }
+    }
+}
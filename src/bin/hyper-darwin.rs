@@ -0,0 +1,213 @@
+use clap::{Parser, Subcommand};
+use hyper_darwin::tasks;
+use hyper_darwin::{
+    load_genome, Experiment, ExperimentConfig, GenerationStats, Genome, HyperDarwinError, Renderer,
+};
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::process;
+
+#[derive(Parser)]
+#[command(name = "hyper-darwin", about = "Runs HyperDarwin experiments")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs an experiment described by a TOML or JSON file
+    Run {
+        config: PathBuf,
+        #[arg(long)]
+        generations: Option<u64>,
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Carries on with an experiment from its checkpoint
+    Resume {
+        checkpoint: PathBuf,
+        /// New total number of generations
+        #[arg(long)]
+        generations: Option<u64>,
+    },
+    /// Writes a saved Genome as a Graphviz DOT graph
+    RenderChampion {
+        genome: PathBuf,
+        /// Defaults to standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Draws the pattern of a saved CPPN as a PNG, or a PGM/PPM for other extensions
+    RenderPattern {
+        genome: PathBuf,
+        output: PathBuf,
+        #[arg(long, default_value_t = 256)]
+        width: usize,
+        #[arg(long, default_value_t = 256)]
+        height: usize,
+        /// Feed the distance from the center after x and y
+        #[arg(long)]
+        distance: bool,
+        /// Feed a constant 1 last
+        #[arg(long)]
+        bias: bool,
+    },
+    /// Prints a summary of a saved Genome
+    InspectGenome { genome: PathBuf },
+    /// Lists the built-in tasks
+    Tasks,
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse().command) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(command: Command) -> Result<(), HyperDarwinError> {
+    match command {
+        Command::Run {
+            config,
+            generations,
+            seed,
+            output_dir,
+        } => {
+            let mut config = ExperimentConfig::load(config)?;
+
+            if let Some(generations) = generations {
+                config.generations = generations;
+            }
+
+            if seed.is_some() {
+                config.seed = seed;
+            }
+
+            if let Some(output_dir) = output_dir {
+                config.output_dir = output_dir;
+            }
+
+            evolve(Experiment::new(config)?)
+        }
+        Command::Resume {
+            checkpoint,
+            generations,
+        } => evolve(Experiment::resume(checkpoint, generations)?),
+        Command::RenderChampion { genome, output } => {
+            let genome = load_genome(genome)?;
+
+            match output {
+                Some(path) => genome.write_dot(BufWriter::new(File::create(path)?))?,
+                None => genome.write_dot(io::stdout().lock())?,
+            }
+
+            Ok(())
+        }
+        Command::RenderPattern {
+            genome,
+            output,
+            width,
+            height,
+            distance,
+            bias,
+        } => Renderer::new(width, height)
+            .distance(distance)
+            .bias(bias)
+            .render(&load_genome(genome)?)?
+            .save(output),
+        Command::InspectGenome { genome } => {
+            inspect(&load_genome(genome)?);
+            Ok(())
+        }
+        Command::Tasks => {
+            tasks::TASKS.iter().for_each(|name| println!("{}", name));
+            Ok(())
+        }
+    }
+}
+
+fn evolve(mut experiment: Experiment) -> Result<(), HyperDarwinError> {
+    let config = experiment.config();
+
+    println!(
+        "task {} with seed {}, writing to {}",
+        config.task,
+        config.seed.unwrap_or_default(),
+        config.output_dir.display()
+    );
+
+    experiment.run(|stats: &GenerationStats| {
+        println!(
+            "gen {:>5}  best {:.4}  mean {:.4}  champion {:.4}  species {:>3}  nodes {:.1}  conns {:.1}{}",
+            stats.generation,
+            stats.best_fitness,
+            stats.mean_fitness,
+            stats.champion_fitness,
+            stats.species,
+            stats.mean_nodes,
+            stats.mean_conns,
+            if stats.solved { "  solved" } else { "" }
+        )
+    })?;
+
+    if experiment.is_solved() {
+        println!("solved");
+    }
+
+    Ok(())
+}
+
+fn inspect(genome: &Genome) {
+    let enabled = genome.conns.iter().filter(|c| c.enabled).count();
+    // Counted from the genes, a hand-edited file may lack some of its inputs
+    // or outputs
+    let last_output = genome.bias_node() + genome.outputs();
+    let hidden = genome
+        .nodes
+        .iter()
+        .filter(|n| n.innov > last_output)
+        .count();
+
+    let mut activations = BTreeMap::<String, usize>::new();
+
+    for node in genome.nodes.iter().filter(|n| n.innov > genome.bias_node()) {
+        *activations
+            .entry(format!("{:?}", node.activation))
+            .or_default() += 1;
+    }
+
+    println!("id                {}", genome.id);
+    println!("parents           {:?}", genome.parents);
+    println!("birth generation  {}", genome.birth_generation);
+    println!("species           {:?}", genome.species_id);
+    println!("fitness           {}", genome.fitness);
+    println!("inputs            {}", genome.inputs());
+    println!("outputs           {}", genome.outputs());
+    println!("hidden nodes      {}", hidden);
+    println!(
+        "connections       {} ({} enabled)",
+        genome.conns.len(),
+        enabled
+    );
+
+    for (activation, count) in activations {
+        println!("  {:<16}{}", activation, count);
+    }
+
+    for conn in &genome.conns {
+        println!(
+            "  {:>5}: {:>4} -> {:<4} {:>8.4}{}",
+            conn.innov,
+            conn.from,
+            conn.to,
+            conn.weight,
+            if conn.enabled { "" } else { "  disabled" }
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::vec::Vec;
+
+const MAX_ITERATIONS: usize = 30;
+
+// Result of partitioning points around medoids. `assignment[i]` is the index
+// into `medoids` of the cluster point `i` belongs to.
+pub struct Clustering {
+    pub medoids: Vec<usize>,
+    pub assignment: Vec<usize>,
+}
+
+impl Clustering {
+    pub fn members(&self, cluster: usize) -> impl Iterator<Item = usize> + '_ {
+        self.assignment
+            .iter()
+            .enumerate()
+            .filter(move |&(_, &c)| c == cluster)
+            .map(|(i, _)| i)
+    }
+}
+
+// Voronoi iteration k-medoids over a precomputed distance matrix. `seeds` are
+// used as the initial medoids, topped up with far away points if there are
+// fewer than `k` of them.
+pub fn k_medoids(dist: &[Vec<f64>], k: usize, seeds: &[usize]) -> Clustering {
+    let n = dist.len();
+    let k = k.clamp(1, n.max(1));
+
+    let mut medoids = Vec::<usize>::with_capacity(k);
+
+    for &seed in seeds {
+        if seed < n && !medoids.contains(&seed) && medoids.len() < k {
+            medoids.push(seed);
+        }
+    }
+
+    if medoids.is_empty() && n > 0 {
+        medoids.push(0);
+    }
+
+    while medoids.len() < k {
+        let farthest = (0..n).filter(|i| !medoids.contains(i)).max_by(|&a, &b| {
+            let da = nearest(dist, &medoids, a).1;
+            let db = nearest(dist, &medoids, b).1;
+            da.total_cmp(&db)
+        });
+
+        match farthest {
+            Some(i) => medoids.push(i),
+            None => break,
+        }
+    }
+
+    let mut assignment = assign(dist, &medoids);
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+
+        for (c, medoid) in medoids.iter_mut().enumerate() {
+            let members = (0..n)
+                .filter(|&i| assignment[i] == c)
+                .collect::<Vec<usize>>();
+
+            let best = members.iter().copied().min_by(|&a, &b| {
+                let cost_a = members.iter().map(|&m| dist[a][m]).sum::<f64>();
+                let cost_b = members.iter().map(|&m| dist[b][m]).sum::<f64>();
+                cost_a.total_cmp(&cost_b)
+            });
+
+            if let Some(best) = best {
+                if best != *medoid {
+                    *medoid = best;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+
+        assignment = assign(dist, &medoids);
+    }
+
+    Clustering {
+        medoids,
+        assignment,
+    }
+}
+
+// Mean silhouette coefficient of a clustering, in [-1, 1], higher is better
+pub fn silhouette(dist: &[Vec<f64>], clustering: &Clustering) -> f64 {
+    let n = dist.len();
+    let k = clustering.medoids.len();
+
+    if n == 0 || k < 2 {
+        return 0.;
+    }
+
+    let mut total = 0.;
+
+    for (i, row) in dist.iter().enumerate() {
+        let mut sums = vec![0.; k];
+        let mut counts = vec![0usize; k];
+
+        for (j, d) in row.iter().enumerate().filter(|&(j, _)| j != i) {
+            sums[clustering.assignment[j]] += d;
+            counts[clustering.assignment[j]] += 1;
+        }
+
+        let own = clustering.assignment[i];
+
+        if counts[own] == 0 {
+            continue;
+        }
+
+        let a = sums[own] / counts[own] as f64;
+        let b = (0..k)
+            .filter(|&c| c != own && counts[c] > 0)
+            .map(|c| sums[c] / counts[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+
+        // Identical points have no spread to separate, a and b are both 0
+        let spread = a.max(b);
+
+        if b.is_finite() && spread > 0. {
+            total += (b - a) / spread;
+        }
+    }
+
+    total / n as f64
+}
+
+fn nearest(dist: &[Vec<f64>], medoids: &[usize], point: usize) -> (usize, f64) {
+    medoids
+        .iter()
+        .enumerate()
+        .map(|(c, &m)| (c, dist[point][m]))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+fn assign(dist: &[Vec<f64>], medoids: &[usize]) -> Vec<usize> {
+    (0..dist.len())
+        .map(|i| match medoids.iter().position(|&m| m == i) {
+            Some(c) => c,
+            None => nearest(dist, medoids, i).0,
+        })
+        .collect()
+}
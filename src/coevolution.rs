@@ -0,0 +1,185 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::population::Population;
+use crate::rng::thread_rng;
+use crate::settings::Settings;
+
+use rand::seq::SliceRandom;
+
+use std::vec::Vec;
+
+// Both counts must be above 0
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OpponentSampling {
+    Random(usize), // This many random members of the opposing population
+    BestOfLastGeneration,
+    HallOfFame(usize), // This many random past champions of the opposing population
+}
+
+// Competitive coevolution of two Populations. Every Genome is scored by playing
+// against opponents sampled from the other Population.
+pub struct Coevolution {
+    pop_a: Population,
+    pop_b: Population,
+    hall_of_fame_a: Vec<Genome>,
+    hall_of_fame_b: Vec<Genome>,
+    last_champ_a: Option<Genome>,
+    last_champ_b: Option<Genome>,
+    pub sampling: OpponentSampling,
+    pub hall_of_fame_size: usize,
+}
+
+impl Coevolution {
+    pub fn new(sets_a: Settings, sets_b: Settings) -> Self {
+        Self {
+            pop_a: Population::new(sets_a),
+            pop_b: Population::new(sets_b),
+            hall_of_fame_a: Vec::new(),
+            hall_of_fame_b: Vec::new(),
+            last_champ_a: None,
+            last_champ_b: None,
+            sampling: OpponentSampling::Random(5),
+            hall_of_fame_size: 50,
+        }
+    }
+
+    pub fn sampling(mut self, sampling: OpponentSampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn hall_of_fame_size(mut self, size: usize) -> Self {
+        self.hall_of_fame_size = size;
+        self
+    }
+
+    pub fn population_a(&mut self) -> &mut Population {
+        &mut self.pop_a
+    }
+
+    pub fn population_b(&mut self) -> &mut Population {
+        &mut self.pop_b
+    }
+
+    pub fn hall_of_fame_a(&self) -> &[Genome] {
+        &self.hall_of_fame_a
+    }
+
+    pub fn hall_of_fame_b(&self) -> &[Genome] {
+        &self.hall_of_fame_b
+    }
+
+    // Plays every Genome against its sampled opponents. `play` receives a Genome
+    // from Population A and one from Population B, in that order, and returns
+    // the score each of them earned. Fitness is the average score over all games.
+    // Fails without playing when the sampling would find no opponents.
+    pub fn evaluate<F>(&mut self, mut play: F) -> Result<(), HyperDarwinError>
+    where
+        F: FnMut(&Genome, &Genome) -> (f64, f64),
+    {
+        match self.sampling {
+            OpponentSampling::Random(0) | OpponentSampling::HallOfFame(0) => {
+                return Err(HyperDarwinError::NoOpponents);
+            }
+            _ => {}
+        }
+
+        let opponents_b = Self::sample_opponents(
+            self.sampling,
+            &self.pop_b,
+            &self.hall_of_fame_b,
+            &self.last_champ_b,
+        );
+
+        for genome in self.pop_a.get_citizens().iter_mut() {
+            let score = opponents_b
+                .iter()
+                .fold(0., |acc, opp| acc + play(genome, opp).0);
+
+            genome.fitness = 0.;
+            genome.add_fitness(score / opponents_b.len().max(1) as f64);
+        }
+
+        let opponents_a = Self::sample_opponents(
+            self.sampling,
+            &self.pop_a,
+            &self.hall_of_fame_a,
+            &self.last_champ_a,
+        );
+
+        for genome in self.pop_b.get_citizens().iter_mut() {
+            let score = opponents_a
+                .iter()
+                .fold(0., |acc, opp| acc + play(opp, genome).1);
+
+            genome.fitness = 0.;
+            genome.add_fitness(score / opponents_a.len().max(1) as f64);
+        }
+
+        Ok(())
+    }
+
+    // Records both champions, then advances both Populations by one generation
+    pub fn next_generation(&mut self) {
+        let champ_a = Self::champion(&self.pop_a);
+        let champ_b = Self::champion(&self.pop_b);
+
+        if let Some(champ) = &champ_a {
+            Self::enshrine(
+                &mut self.hall_of_fame_a,
+                champ.clone(),
+                self.hall_of_fame_size,
+            );
+        }
+
+        if let Some(champ) = &champ_b {
+            Self::enshrine(
+                &mut self.hall_of_fame_b,
+                champ.clone(),
+                self.hall_of_fame_size,
+            );
+        }
+
+        self.last_champ_a = champ_a;
+        self.last_champ_b = champ_b;
+
+        self.pop_a.next_generation();
+        self.pop_b.next_generation();
+    }
+
+    fn champion(pop: &Population) -> Option<Genome> {
+        pop.citizens()
+            .iter()
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+            .cloned()
+    }
+
+    fn enshrine(hall: &mut Vec<Genome>, champ: Genome, size: usize) {
+        hall.push(champ);
+
+        if size > 0 && hall.len() > size {
+            hall.remove(0);
+        }
+    }
+
+    fn sample_opponents(
+        sampling: OpponentSampling,
+        pop: &Population,
+        hall_of_fame: &[Genome],
+        last_champ: &Option<Genome>,
+    ) -> Vec<Genome> {
+        let (pool, amt) = match sampling {
+            OpponentSampling::Random(amt) => (pop.citizens(), amt),
+            OpponentSampling::BestOfLastGeneration => match last_champ {
+                Some(champ) => return vec![champ.clone()],
+                None => (pop.citizens(), 1),
+            },
+            OpponentSampling::HallOfFame(amt) if !hall_of_fame.is_empty() => (hall_of_fame, amt),
+            OpponentSampling::HallOfFame(amt) => (pop.citizens(), amt),
+        };
+
+        pool.choose_multiple(&mut thread_rng(), amt)
+            .cloned()
+            .collect()
+    }
+}
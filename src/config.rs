@@ -0,0 +1,233 @@
+use crate::settings::{HyperSettings, Settings};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+// A single invalid value found while validating Settings or HyperSettings
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsError {
+    RateOutOfRange { field: &'static str, value: f64 }, // Must lie within [0, 1]
+    NotPositive { field: &'static str, value: f64 },
+    Negative { field: &'static str, value: f64 },
+    InvalidWeightRange { min_weight: f64, max_weight: f64 },
+    InvalidSpeciesRange { min: usize, max: usize },
+    TooManyElites { field: &'static str, elites: usize }, // Must be below pop_size
+    EmptyActivationSet,
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::RateOutOfRange { field, value } => {
+                write!(f, "{} must lie within [0, 1], got {}", field, value)
+            }
+            SettingsError::NotPositive { field, value } => {
+                write!(f, "{} must be positive, got {}", field, value)
+            }
+            SettingsError::Negative { field, value } => {
+                write!(f, "{} must not be negative, got {}", field, value)
+            }
+            SettingsError::InvalidWeightRange {
+                min_weight,
+                max_weight,
+            } => write!(
+                f,
+                "min_weight ({}) must be smaller than max_weight ({})",
+                min_weight, max_weight
+            ),
+            SettingsError::InvalidSpeciesRange { min, max } => write!(
+                f,
+                "species range must satisfy 0 < min <= max, got min {} and max {}",
+                min, max
+            ),
+            SettingsError::TooManyElites { field, elites } => {
+                write!(f, "{} ({}) must be smaller than pop_size", field, elites)
+            }
+            SettingsError::EmptyActivationSet => {
+                write!(f, "a custom activation set needs at least one activation")
+            }
+        }
+    }
+}
+
+impl Error for SettingsError {}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    // Picks the format from a `.toml` or `.json` extension
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            _ => Err(ConfigError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    UnknownFormat(PathBuf),
+    Parse(String),
+    Serialize(String),
+    Invalid(Vec<SettingsError>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not access the configuration: {}", err),
+            ConfigError::UnknownFormat(path) => {
+                write!(f, "{} is neither a .toml nor a .json file", path.display())
+            }
+            ConfigError::Parse(msg) => write!(f, "could not parse the configuration: {}", msg),
+            ConfigError::Serialize(msg) => {
+                write!(f, "could not write the configuration: {}", msg)
+            }
+            ConfigError::Invalid(errors) => {
+                write!(f, "invalid configuration:")?;
+
+                for err in errors {
+                    write!(f, "\n  {}", err)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<Vec<SettingsError>> for ConfigError {
+    fn from(errors: Vec<SettingsError>) -> Self {
+        ConfigError::Invalid(errors)
+    }
+}
+
+pub fn parse<T: DeserializeOwned>(text: &str, format: ConfigFormat) -> Result<T, ConfigError> {
+    match format {
+        ConfigFormat::Toml => toml::from_str(text).map_err(|e| ConfigError::Parse(e.to_string())),
+        ConfigFormat::Json => {
+            serde_json::from_str(text).map_err(|e| ConfigError::Parse(e.to_string()))
+        }
+    }
+}
+
+pub fn dump<T: Serialize>(value: &T, format: ConfigFormat) -> Result<String, ConfigError> {
+    match format {
+        ConfigFormat::Toml => {
+            toml::to_string_pretty(value).map_err(|e| ConfigError::Serialize(e.to_string()))
+        }
+        ConfigFormat::Json => {
+            serde_json::to_string_pretty(value).map_err(|e| ConfigError::Serialize(e.to_string()))
+        }
+    }
+}
+
+pub fn load<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, ConfigError> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path)?;
+
+    parse(&fs::read_to_string(path)?, format)
+}
+
+pub fn save<T: Serialize, P: AsRef<Path>>(value: &T, path: P) -> Result<(), ConfigError> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path)?;
+
+    fs::write(path, dump(value, format)?)?;
+
+    Ok(())
+}
+
+impl Settings {
+    // Missing keys take their default values, unknown keys are rejected
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        let sets: Self = parse(text, ConfigFormat::Toml)?;
+        sets.validate()?;
+        Ok(sets)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        let sets: Self = parse(text, ConfigFormat::Json)?;
+        sets.validate()?;
+        Ok(sets)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let sets: Self = load(path)?;
+        sets.validate()?;
+        Ok(sets)
+    }
+
+    // The effective configuration, defaults included
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        dump(self, ConfigFormat::Toml)
+    }
+
+    pub fn to_json(&self) -> Result<String, ConfigError> {
+        dump(self, ConfigFormat::Json)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        save(self, path)
+    }
+}
+
+impl HyperSettings {
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        let sets: Self = parse(text, ConfigFormat::Toml)?;
+        sets.validate()?;
+        Ok(sets)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        let sets: Self = parse(text, ConfigFormat::Json)?;
+        sets.validate()?;
+        Ok(sets)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let sets: Self = load(path)?;
+        sets.validate()?;
+        Ok(sets)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        dump(self, ConfigFormat::Toml)
+    }
+
+    pub fn to_json(&self) -> Result<String, ConfigError> {
+        dump(self, ConfigFormat::Json)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        save(self, path)
+    }
+}
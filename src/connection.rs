@@ -1,3 +1,4 @@
+use crate::rng::thread_rng;
 use crate::settings::Settings;
 
 use std::clone::Clone;
@@ -5,9 +6,10 @@ use std::cmp::PartialEq;
 use std::fmt;
 
 use rand::prelude::*;
-use rand::{self, thread_rng};
 use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize)]
 pub struct Connection {
     pub innov: u32,
     pub weight: f64,
@@ -42,15 +44,13 @@ impl Clone for Connection {
 
 impl Connection {
     pub fn new(innov: u32, from: u32, to: u32, weight: f64, enabled: bool) -> Self {
-        let conn = Self {
+        Self {
             innov,
             from,
             to,
             weight,
             enabled,
-        };
-
-        conn
+        }
     }
 
     pub fn enable(&mut self) {
@@ -66,12 +66,7 @@ impl Connection {
 
         if rng.gen::<f64>() < sets.wt_shift_rate {
             self.weight += Normal::new(0., 0.04).unwrap().sample(&mut rng);
-
-            if self.weight < -1. {
-                self.weight = -1.;
-            } else if self.weight > 1. {
-                self.weight = 1.
-            }
+            self.weight = self.weight.clamp(-1., 1.);
         } else {
             self.weight = rng.gen::<f64>() * 2. - 1.;
         }
@@ -0,0 +1,226 @@
+use crate::connection::Connection;
+use crate::genome::Genome;
+use crate::settings::Settings;
+
+use std::collections::HashMap;
+use std::f64;
+use std::sync::Arc;
+use std::vec::Vec;
+
+// Genomic distance used to decide whether two Genomes belong to the same species.
+// The built-in metrics put Genomes with connections but none in common
+// infinitely far apart, while two Genomes without any are compared on their
+// nodes alone.
+pub trait CompatibilityDistance: Send + Sync {
+    fn distance(&self, a: &Genome, b: &Genome, sets: &Settings) -> f64;
+
+    // Name the metric is written under in configuration files. Only the
+    // built-in metrics can be read back.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+}
+
+// Built-in metric with the given configuration name
+pub fn by_name(name: &str) -> Option<Arc<dyn CompatibilityDistance>> {
+    match name {
+        "neat" => Some(Arc::new(NeatDistance)),
+        "node_gene" => Some(Arc::new(NodeGeneDistance)),
+        "parameter" => Some(Arc::new(ParameterDistance)),
+        _ => None,
+    }
+}
+
+// Connection gene alignment of two Genomes by innovation number
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct GeneComparison {
+    pub matching: f64,
+    pub disjoint: f64,
+    pub excess: f64,
+    pub weight_difference: f64,
+}
+
+impl GeneComparison {
+    pub fn new(a: &[Connection], b: &[Connection]) -> Self {
+        let mut genes1 = a.iter().collect::<Vec<&Connection>>();
+        let mut genes2 = b.iter().collect::<Vec<&Connection>>();
+        genes1.sort_unstable_by_key(|c| c.innov);
+        genes2.sort_unstable_by_key(|c| c.innov);
+
+        let max1 = genes1.last().map_or(0, |c| c.innov);
+        let max2 = genes2.last().map_or(0, |c| c.innov);
+
+        let mut res = Self::default();
+        let (mut i, mut j) = (0, 0);
+
+        while i < genes1.len() || j < genes2.len() {
+            match (genes1.get(i), genes2.get(j)) {
+                (Some(g1), Some(g2)) if g1.innov == g2.innov => {
+                    res.matching += 1.;
+                    res.weight_difference += (g1.weight - g2.weight).abs();
+                    i += 1;
+                    j += 1;
+                }
+                (Some(g1), g2) if g2.is_none_or(|g2| g1.innov < g2.innov) => {
+                    if g1.innov > max2 {
+                        res.excess += 1.;
+                    } else {
+                        res.disjoint += 1.;
+                    }
+                    i += 1;
+                }
+                (_, Some(g2)) => {
+                    if g2.innov > max1 {
+                        res.excess += 1.;
+                    } else {
+                        res.disjoint += 1.;
+                    }
+                    j += 1;
+                }
+                (_, None) => break,
+            }
+        }
+
+        res
+    }
+
+    // Connections exist but none of them line up
+    pub fn unrelated(&self) -> bool {
+        self.matching == 0. && self.disjoint + self.excess > 0.
+    }
+
+    // 0 without matching genes
+    pub fn mean_weight_difference(&self) -> f64 {
+        if self.matching == 0. {
+            0.
+        } else {
+            self.weight_difference / self.matching
+        }
+    }
+}
+
+// The original NEAT metric. Genomes with fewer than 20 genes are not normalized
+// and activations are only compared over the nodes of the first Genome.
+pub struct NeatDistance;
+
+impl CompatibilityDistance for NeatDistance {
+    fn name(&self) -> &'static str {
+        "neat"
+    }
+
+    fn distance(&self, a: &Genome, b: &Genome, sets: &Settings) -> f64 {
+        let genes = GeneComparison::new(&a.conns, &b.conns);
+
+        if genes.unrelated() {
+            return f64::INFINITY;
+        }
+
+        let differing_activs = a
+            .nodes
+            .iter()
+            .filter(|n1| {
+                b.nodes
+                    .iter()
+                    .find(|n2| n2.innov == n1.innov)
+                    .is_some_and(|n2| n2.activation != n1.activation)
+            })
+            .count() as f64;
+
+        let n = a.conns.len().max(b.conns.len()) as f64;
+        let n = if n < 20. { 1. } else { n };
+
+        (sets.disjoint_coeff * genes.disjoint / n)
+            + (sets.excess_coeff * genes.excess / n)
+            + (sets.weight_coeff * genes.mean_weight_difference())
+            + (sets.activation_coeff * differing_activs / n)
+    }
+}
+
+// NEAT metric that also counts node genes present in only one of the Genomes.
+// Everything is normalized by genome size and activations are compared over
+// all shared nodes.
+pub struct NodeGeneDistance;
+
+impl CompatibilityDistance for NodeGeneDistance {
+    fn name(&self) -> &'static str {
+        "node_gene"
+    }
+
+    fn distance(&self, a: &Genome, b: &Genome, sets: &Settings) -> f64 {
+        let genes = GeneComparison::new(&a.conns, &b.conns);
+
+        if genes.unrelated() {
+            return f64::INFINITY;
+        }
+
+        let b_nodes = b
+            .nodes
+            .iter()
+            .map(|n| (n.innov, n))
+            .collect::<HashMap<u32, _>>();
+
+        let mut shared_nodes = 0.;
+        let mut differing_activs = 0.;
+
+        for node in &a.nodes {
+            if let Some(other) = b_nodes.get(&node.innov) {
+                shared_nodes += 1.;
+
+                if other.activation != node.activation {
+                    differing_activs += 1.;
+                }
+            }
+        }
+
+        let disjoint_nodes = (a.nodes.len() + b.nodes.len()) as f64 - 2. * shared_nodes;
+
+        let n_conns = a.conns.len().max(b.conns.len()).max(1) as f64;
+        let n_nodes = a.nodes.len().max(b.nodes.len()).max(1) as f64;
+
+        (sets.disjoint_coeff * genes.disjoint / n_conns)
+            + (sets.excess_coeff * genes.excess / n_conns)
+            + (sets.weight_coeff * genes.mean_weight_difference())
+            + (sets.node_coeff * disjoint_nodes / n_nodes)
+            + (sets.activation_coeff * differing_activs / n_nodes)
+    }
+}
+
+// NodeGeneDistance plus a separate term for the weights leaving the bias node,
+// which act as the per-node biases of the CPPN
+pub struct ParameterDistance;
+
+impl CompatibilityDistance for ParameterDistance {
+    fn name(&self) -> &'static str {
+        "parameter"
+    }
+
+    fn distance(&self, a: &Genome, b: &Genome, sets: &Settings) -> f64 {
+        let structural = NodeGeneDistance.distance(a, b, sets);
+
+        if structural.is_infinite() {
+            return structural;
+        }
+
+        let bias = a.bias_node();
+
+        let b_bias = b
+            .conns
+            .iter()
+            .filter(|c| c.from == bias)
+            .map(|c| (c.to, c.weight))
+            .collect::<HashMap<u32, f64>>();
+
+        let (bias_difference, shared) = a
+            .conns
+            .iter()
+            .filter(|c| c.from == bias)
+            .filter_map(|c| b_bias.get(&c.to).map(|w| (c.weight - w).abs()))
+            .fold((0., 0.), |(diff, n), d| (diff + d, n + 1.));
+
+        if shared == 0. {
+            return structural;
+        }
+
+        structural + sets.bias_coeff * bias_difference / shared
+    }
+}
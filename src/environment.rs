@@ -0,0 +1,273 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::hyper_tensor::HyperTensor;
+use crate::substrate::{Network, Substrate};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::vec::Vec;
+
+// Bounds of every value of an observation or action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Space {
+    pub low: Vec<f64>,
+    pub high: Vec<f64>,
+}
+
+impl Space {
+    pub fn new(low: Vec<f64>, high: Vec<f64>) -> Self {
+        Self { low, high }
+    }
+
+    // `size` values sharing the same bounds
+    pub fn uniform(size: usize, low: f64, high: f64) -> Self {
+        Self::new(vec![low; size], vec![high; size])
+    }
+
+    pub fn size(&self) -> usize {
+        self.low.len()
+    }
+
+    pub fn contains(&self, values: &[f64]) -> bool {
+        values.len() == self.size()
+            && values
+                .iter()
+                .zip(self.low.iter().zip(self.high.iter()))
+                .all(|(v, (low, high))| v >= low && v <= high)
+    }
+
+    pub fn clip(&self, values: &mut [f64]) {
+        for (v, (&low, &high)) in values.iter_mut().zip(self.low.iter().zip(self.high.iter())) {
+            *v = v.clamp(low, high);
+        }
+    }
+}
+
+// What an Environment returns after each action
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub observation: Vec<f64>,
+    pub reward: f64,
+    pub done: bool, // The episode is over and the Environment needs a reset
+}
+
+// An episodic control task driven one action at a time
+pub trait Environment {
+    fn observation_space(&self) -> Space;
+
+    fn action_space(&self) -> Space;
+
+    // Starts a new episode, returning its first observation
+    fn reset(&mut self) -> Vec<f64>;
+
+    fn step(&mut self, action: &[f64]) -> Step;
+}
+
+// The network acting in an Environment. A Genome is used directly, while a
+// decoded Substrate takes observations laid out row by row over its planes.
+#[derive(Clone, Copy)]
+pub enum Controller<'a> {
+    Genome(&'a Genome),
+    Network(&'a Network),
+    Substrate(&'a Substrate),
+}
+
+impl Controller<'_> {
+    pub fn act(&self, observation: &[f64]) -> Result<Vec<f64>, HyperDarwinError> {
+        self.act_with_memory(observation, &mut HashMap::new())
+    }
+
+    // Like `act`, keeping what a recurrent Genome remembers in `memory` from
+    // one step of an episode to the next
+    pub fn act_with_memory(
+        &self,
+        observation: &[f64],
+        memory: &mut HashMap<u32, f64>,
+    ) -> Result<Vec<f64>, HyperDarwinError> {
+        match self {
+            Controller::Genome(genome) => genome.activate(observation, memory),
+            Controller::Network(network) => network.activate(observation),
+            Controller::Substrate(substrate) => {
+                if observation.len() != substrate.m * substrate.n {
+                    return Err(HyperDarwinError::InputSizeMismatch {
+                        expected: substrate.m * substrate.n,
+                        actual: observation.len(),
+                    });
+                }
+
+                let input = HyperTensor::new(
+                    observation
+                        .chunks(substrate.n)
+                        .map(|row| row.to_vec())
+                        .collect(),
+                )?;
+
+                Ok(substrate.activate(&input)?.concat())
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a Genome> for Controller<'a> {
+    fn from(genome: &'a Genome) -> Self {
+        Controller::Genome(genome)
+    }
+}
+
+impl<'a> From<&'a Network> for Controller<'a> {
+    fn from(network: &'a Network) -> Self {
+        Controller::Network(network)
+    }
+}
+
+impl<'a> From<&'a Substrate> for Controller<'a> {
+    fn from(substrate: &'a Substrate) -> Self {
+        Controller::Substrate(substrate)
+    }
+}
+
+// How the returns of the episodes of a trial make up its fitness
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Aggregation {
+    Mean,
+    Sum,
+    Min, // Rewards controllers that never do badly
+}
+
+// Environments that never set `done` still stop after this many steps
+pub const DEFAULT_MAX_STEPS: u64 = 10_000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Episode {
+    pub total_reward: f64,
+    pub steps: u64,
+}
+
+// Plays episodes of an Environment with a Controller. Every trial plays
+// `episodes` episodes whose returns are aggregated, and the fitness is the mean
+// over `trials` trials, which smooths out noisy Environments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EpisodeRunner {
+    pub episodes: usize,
+    pub trials: usize,
+    pub max_steps: u64, // Ends episodes the Environment doesn't end itself
+    pub aggregation: Aggregation,
+}
+
+impl Default for EpisodeRunner {
+    fn default() -> Self {
+        Self {
+            episodes: 1,
+            trials: 1,
+            max_steps: DEFAULT_MAX_STEPS,
+            aggregation: Aggregation::Mean,
+        }
+    }
+}
+
+impl EpisodeRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn episodes(mut self, episodes: usize) -> Self {
+        self.episodes = episodes;
+        self
+    }
+
+    pub fn trials(mut self, trials: usize) -> Self {
+        self.trials = trials;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    // Plays a single episode from a reset, clipping actions to the action space
+    pub fn episode<'a, E, C>(&self, env: &mut E, controller: C) -> Result<Episode, HyperDarwinError>
+    where
+        E: Environment + ?Sized,
+        C: Into<Controller<'a>>,
+    {
+        let controller = controller.into();
+        let action_space = env.action_space();
+
+        let mut memory = HashMap::new();
+        let mut observation = env.reset();
+        let mut episode = Episode {
+            total_reward: 0.,
+            steps: 0,
+        };
+
+        while episode.steps < self.max_steps {
+            let mut action = controller.act_with_memory(&observation, &mut memory)?;
+
+            if action.len() != action_space.size() {
+                return Err(HyperDarwinError::ActionSizeMismatch {
+                    expected: action_space.size(),
+                    actual: action.len(),
+                });
+            }
+
+            action_space.clip(&mut action);
+
+            let step = env.step(&action);
+
+            episode.total_reward += step.reward;
+            episode.steps += 1;
+            observation = step.observation;
+
+            if step.done {
+                break;
+            }
+        }
+
+        Ok(episode)
+    }
+
+    // Aggregated returns of every trial
+    pub fn trial_returns<'a, E, C>(
+        &self,
+        env: &mut E,
+        controller: C,
+    ) -> Result<Vec<f64>, HyperDarwinError>
+    where
+        E: Environment + ?Sized,
+        C: Into<Controller<'a>>,
+    {
+        let controller = controller.into();
+
+        (0..self.trials.max(1))
+            .map(|_| {
+                let returns = (0..self.episodes.max(1))
+                    .map(|_| Ok(self.episode(env, controller)?.total_reward))
+                    .collect::<Result<Vec<f64>, HyperDarwinError>>()?;
+
+                Ok(match self.aggregation {
+                    Aggregation::Mean => returns.iter().sum::<f64>() / returns.len() as f64,
+                    Aggregation::Sum => returns.iter().sum(),
+                    Aggregation::Min => returns.iter().cloned().fold(f64::INFINITY, f64::min),
+                })
+            })
+            .collect()
+    }
+
+    pub fn run<'a, E, C>(&self, env: &mut E, controller: C) -> Result<f64, HyperDarwinError>
+    where
+        E: Environment + ?Sized,
+        C: Into<Controller<'a>>,
+    {
+        let returns = self.trial_returns(env, controller)?;
+
+        Ok(returns.iter().sum::<f64>() / returns.len() as f64)
+    }
+}
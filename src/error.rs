@@ -0,0 +1,245 @@
+use crate::config::{ConfigError, SettingsError};
+use crate::tasks::MAX_PARITY_BITS;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::vec::Vec;
+
+#[derive(Debug)]
+pub enum HyperDarwinError {
+    InputSizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    // Both need to be at least 2
+    InvalidTensorShape {
+        rows: usize,
+        cols: usize,
+    },
+    RaggedTensor {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    // A Controller gave an action of the wrong size for its Environment
+    ActionSizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidLayers(Vec<usize>), // A Network needs at least 2 layers, none of them empty
+    // (row, col) of a point and the (rows, cols) of the plane it should lie in
+    PointOutOfPlane {
+        point: (usize, usize),
+        plane: (usize, usize),
+    },
+    MissingThirdParamFn,
+    UnexpectedThirdParamFn,
+    MissingNode(u32),      // A connection refers to a node the Genome doesn't have
+    UnreachableNode(u32),  // A node got no value while feeding forward
+    NoOutputs,             // The Genome has no output node to read
+    NoConnections,         // There is no connection to split
+    NoConnectionAvailable, // Every possible connection already exists
+    NoIslands,
+    EmptyPopulation, // There are no Genomes to breed from
+    NoOpponents,     // Coevolution was asked to sample 0 opponents
+    // Genome `genome` scores `actual` objectives while the first one scores `expected`
+    ObjectiveCountMismatch {
+        genome: usize,
+        expected: usize,
+        actual: usize,
+    },
+    // A MAP-Elites dimension needs finite bounds with min below max
+    InvalidDimension {
+        min: f64,
+        max: f64,
+    },
+    ZeroResolution, // A MAP-Elites dimension needs at least one bin
+    TooManyCells,   // The resolutions multiply to more cells than a usize counts
+    // (inputs, outputs) of the first island and of the odd one out
+    IncompatibleIslands {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    // (inputs, outputs) the Settings ask for and those of the Genome
+    IncompatibleGenome {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    NothingSelected,
+    SelectionOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    InvalidSettings(Vec<SettingsError>),
+    Config(ConfigError),
+    UnknownTask(String),
+    InvalidParityBits(u32), // Parity takes 1 to MAX_PARITY_BITS inputs
+    // Line 0 when the maze as a whole is missing something
+    InvalidMaze {
+        line: usize,
+        reason: String,
+    },
+    Io(io::Error),
+    Serialization(String), // Reading or writing Genomes and checkpoints
+}
+
+impl fmt::Display for HyperDarwinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HyperDarwinError::InputSizeMismatch { expected, actual } => {
+                write!(f, "expected an input of size {}, got {}", expected, actual)
+            }
+            HyperDarwinError::InvalidTensorShape { rows, cols } => write!(
+                f,
+                "a HyperTensor needs at least 2 rows and 2 columns, got {}x{}",
+                rows, cols
+            ),
+            HyperDarwinError::RaggedTensor {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "row {} of the HyperTensor has {} columns instead of {}",
+                row, actual, expected
+            ),
+            HyperDarwinError::ActionSizeMismatch { expected, actual } => write!(
+                f,
+                "the Environment expects actions of size {}, got {}",
+                expected, actual
+            ),
+            HyperDarwinError::InvalidLayers(sizes) => write!(
+                f,
+                "a Network needs at least 2 non-empty layers, got layers of sizes {:?}",
+                sizes
+            ),
+            HyperDarwinError::PointOutOfPlane { point, plane } => write!(
+                f,
+                "point ({}, {}) lies outside of a {}x{} plane",
+                point.0, point.1, plane.0, plane.1
+            ),
+            HyperDarwinError::MissingThirdParamFn => {
+                write!(f, "expected a third parameter function, got none")
+            }
+            HyperDarwinError::UnexpectedThirdParamFn => {
+                write!(f, "didn't expect a third parameter function")
+            }
+            HyperDarwinError::MissingNode(node) => {
+                write!(f, "the Genome has no node {}", node)
+            }
+            HyperDarwinError::UnreachableNode(node) => {
+                write!(f, "node {} received no value while feeding forward", node)
+            }
+            HyperDarwinError::NoOutputs => write!(f, "the Genome has no outputs"),
+            HyperDarwinError::NoConnections => write!(f, "the Genome has no connections"),
+            HyperDarwinError::NoConnectionAvailable => {
+                write!(f, "every possible connection already exists")
+            }
+            HyperDarwinError::NoIslands => write!(f, "an Archipelago needs at least one island"),
+            HyperDarwinError::NoOpponents => {
+                write!(f, "every Genome needs to play at least one opponent")
+            }
+            HyperDarwinError::EmptyPopulation => {
+                write!(f, "cannot breed a generation from an empty Population")
+            }
+            HyperDarwinError::ObjectiveCountMismatch {
+                genome,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Genome {} scores {} objectives instead of {}",
+                genome, actual, expected
+            ),
+            HyperDarwinError::InvalidDimension { min, max } => write!(
+                f,
+                "a dimension needs finite bounds with min below max, got {} and {}",
+                min, max
+            ),
+            HyperDarwinError::ZeroResolution => {
+                write!(f, "a dimension needs a resolution of at least 1")
+            }
+            HyperDarwinError::TooManyCells => write!(f, "the dimensions make too many cells"),
+            HyperDarwinError::IncompatibleIslands { expected, actual } => write!(
+                f,
+                "all islands must evolve Genomes with {} inputs and {} outputs, got {} and {}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            HyperDarwinError::IncompatibleGenome { expected, actual } => write!(
+                f,
+                "expected a Genome with {} inputs and {} outputs, got {} and {}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            HyperDarwinError::NothingSelected => {
+                write!(f, "select at least one Genome to breed the next generation")
+            }
+            HyperDarwinError::SelectionOutOfRange { index, len } => write!(
+                f,
+                "cannot select Genome {} of a generation of {}",
+                index, len
+            ),
+            HyperDarwinError::InvalidSettings(errors) => {
+                write!(f, "invalid settings:")?;
+
+                for err in errors {
+                    write!(f, "\n  {}", err)?;
+                }
+
+                Ok(())
+            }
+            HyperDarwinError::Config(err) => write!(f, "{}", err),
+            HyperDarwinError::UnknownTask(name) => write!(f, "there is no task called {}", name),
+            HyperDarwinError::InvalidParityBits(bits) => write!(
+                f,
+                "parity takes 1 to {} bits, got {}",
+                MAX_PARITY_BITS, bits
+            ),
+            HyperDarwinError::InvalidMaze { line: 0, reason } => {
+                write!(f, "invalid maze: {}", reason)
+            }
+            HyperDarwinError::InvalidMaze { line, reason } => {
+                write!(f, "invalid maze at line {}: {}", line, reason)
+            }
+            HyperDarwinError::Io(err) => write!(f, "{}", err),
+            HyperDarwinError::Serialization(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for HyperDarwinError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HyperDarwinError::Config(err) => Some(err),
+            HyperDarwinError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<Vec<SettingsError>> for HyperDarwinError {
+    fn from(errors: Vec<SettingsError>) -> Self {
+        HyperDarwinError::InvalidSettings(errors)
+    }
+}
+
+impl From<ConfigError> for HyperDarwinError {
+    fn from(err: ConfigError) -> Self {
+        match err {
+            ConfigError::Invalid(errors) => HyperDarwinError::InvalidSettings(errors),
+            err => HyperDarwinError::Config(err),
+        }
+    }
+}
+
+impl From<io::Error> for HyperDarwinError {
+    fn from(err: io::Error) -> Self {
+        HyperDarwinError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for HyperDarwinError {
+    fn from(err: serde_json::Error) -> Self {
+        HyperDarwinError::Serialization(err.to_string())
+    }
+}
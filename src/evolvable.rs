@@ -0,0 +1,211 @@
+use crate::genome::Genome;
+use crate::history::History;
+use crate::lineage::Mutation;
+use crate::settings::Settings;
+
+// An encoding that a Population can evolve. Only the genetic operators and
+// fitness are required. The lineage, novelty and multi-objective bookkeeping
+// default to doing nothing, so encodings that skip them simply don't show up
+// in the genealogy, score no novelty and all share the first Pareto front.
+pub trait Evolvable: Clone {
+    // A fresh, randomly initialized member of the starting population
+    fn initial(sets: &Settings) -> Self;
+
+    fn mutate(&mut self, hist: &mut History, sets: &Settings);
+
+    fn crossover(parent1: &Self, parent2: &Self, sets: &Settings) -> Self;
+
+    // An asexual copy, counted as a new individual
+    fn offspring(&self) -> Self;
+
+    fn distance(&self, other: &Self, sets: &Settings) -> f64;
+
+    fn fitness(&self) -> f64;
+
+    fn set_fitness(&mut self, fitness: f64);
+
+    // The fitness the task gave, kept while novelty, Pareto ranking and
+    // fitness sharing rewrite `fitness`. Champions and histories use it.
+    fn objective_fitness(&self) -> f64 {
+        self.fitness()
+    }
+
+    fn set_objective_fitness(&mut self, _fitness: f64) {}
+
+    // Wipes everything set while evaluating, before the next evaluation
+    fn clear_evaluation(&mut self) {
+        self.set_fitness(0.);
+    }
+
+    // Whether two individuals carry identical genes, keeps duplicates out of
+    // the hall of fame
+    fn same_genes(&self, _other: &Self) -> bool {
+        false
+    }
+
+    fn id(&self) -> u64 {
+        0
+    }
+
+    fn parents(&self) -> &[u64] {
+        &[]
+    }
+
+    fn mutations(&self) -> &[Mutation] {
+        &[]
+    }
+
+    fn birth_generation(&self) -> u64 {
+        0
+    }
+
+    fn set_birth_generation(&mut self, _generation: u64) {}
+
+    fn species_id(&self) -> Option<usize> {
+        None
+    }
+
+    fn set_species_id(&mut self, _species: Option<usize>) {}
+
+    fn behavior(&self) -> &[f64] {
+        &[]
+    }
+
+    fn set_behavior(&mut self, _behavior: Vec<f64>) {}
+
+    fn novelty(&self) -> f64 {
+        0.
+    }
+
+    fn set_novelty(&mut self, _novelty: f64) {}
+
+    fn objectives(&self) -> &[f64] {
+        &[]
+    }
+
+    fn pareto_rank(&self) -> usize {
+        0
+    }
+
+    fn crowding_distance(&self) -> f64 {
+        0.
+    }
+
+    fn set_pareto_rank(&mut self, _rank: usize, _crowding_distance: f64) {}
+}
+
+impl Evolvable for Genome {
+    fn initial(sets: &Settings) -> Self {
+        Genome::from_settings(sets)
+    }
+
+    fn mutate(&mut self, hist: &mut History, sets: &Settings) {
+        Genome::mutate(self, hist, sets)
+    }
+
+    fn crossover(parent1: &Self, parent2: &Self, sets: &Settings) -> Self {
+        Genome::crossover(parent1, parent2, sets)
+    }
+
+    fn offspring(&self) -> Self {
+        Genome::offspring(self)
+    }
+
+    fn distance(&self, other: &Self, sets: &Settings) -> f64 {
+        Genome::distance(self, other, sets)
+    }
+
+    fn fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+
+    fn objective_fitness(&self) -> f64 {
+        self.objective_fitness
+    }
+
+    fn set_objective_fitness(&mut self, fitness: f64) {
+        self.objective_fitness = fitness;
+    }
+
+    fn clear_evaluation(&mut self) {
+        Genome::clear_evaluation(self)
+    }
+
+    fn same_genes(&self, other: &Self) -> bool {
+        self.nodes.len() == other.nodes.len()
+            && self.conns.len() == other.conns.len()
+            && self
+                .nodes
+                .iter()
+                .zip(other.nodes.iter())
+                .all(|(n1, n2)| n1.innov == n2.innov && n1.activation == n2.activation)
+            && self.conns.iter().zip(other.conns.iter()).all(|(c1, c2)| {
+                c1.innov == c2.innov && c1.weight == c2.weight && c1.enabled == c2.enabled
+            })
+    }
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn parents(&self) -> &[u64] {
+        &self.parents
+    }
+
+    fn mutations(&self) -> &[Mutation] {
+        &self.mutations
+    }
+
+    fn birth_generation(&self) -> u64 {
+        self.birth_generation
+    }
+
+    fn set_birth_generation(&mut self, generation: u64) {
+        self.birth_generation = generation;
+    }
+
+    fn species_id(&self) -> Option<usize> {
+        self.species_id
+    }
+
+    fn set_species_id(&mut self, species: Option<usize>) {
+        self.species_id = species;
+    }
+
+    fn behavior(&self) -> &[f64] {
+        &self.behavior
+    }
+
+    fn set_behavior(&mut self, behavior: Vec<f64>) {
+        Genome::set_behavior(self, behavior)
+    }
+
+    fn novelty(&self) -> f64 {
+        self.novelty
+    }
+
+    fn set_novelty(&mut self, novelty: f64) {
+        self.novelty = novelty;
+    }
+
+    fn objectives(&self) -> &[f64] {
+        &self.objectives
+    }
+
+    fn pareto_rank(&self) -> usize {
+        self.pareto_rank
+    }
+
+    fn crowding_distance(&self) -> f64 {
+        self.crowding_distance
+    }
+
+    fn set_pareto_rank(&mut self, rank: usize, crowding_distance: f64) {
+        self.pareto_rank = rank;
+        self.crowding_distance = crowding_distance;
+    }
+}
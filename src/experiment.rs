@@ -0,0 +1,327 @@
+use crate::config::{self, ConfigError, ConfigFormat};
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::population::Population;
+use crate::rng;
+use crate::settings::{HyperSettings, Settings};
+use crate::tasks::{self, Task};
+
+use rand::random;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+const CHAMPION_FILE: &str = "champion.json";
+const STATS_FILE: &str = "stats.csv";
+const CONFIG_FILE: &str = "config.toml";
+
+// Everything describing a run, loadable from a TOML or JSON file
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExperimentConfig {
+    pub task: String,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub task_params: Value, // Options of the task, missing ones take their defaults
+    pub seed: Option<u64>, // Drawn at random and recorded when left out
+    pub generations: u64,
+    pub output_dir: PathBuf,
+    pub checkpoint_interval: u64, // 0 only checkpoints at the end of the run
+    pub stop_when_solved: bool,
+    pub settings: Settings,
+    pub hyper: HyperSettings,
+}
+
+impl Default for ExperimentConfig {
+    fn default() -> Self {
+        Self {
+            task: String::from("sine"),
+            task_params: Value::Null,
+            seed: None,
+            generations: 100,
+            output_dir: PathBuf::from("runs"),
+            checkpoint_interval: 10,
+            stop_when_solved: true,
+            settings: Settings::default(),
+            hyper: HyperSettings::default(),
+        }
+    }
+}
+
+impl ExperimentConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, HyperDarwinError> {
+        let config: Self = config::load(path)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), HyperDarwinError> {
+        let mut errors = Vec::new();
+
+        if let Err(mut errs) = self.settings.validate() {
+            errors.append(&mut errs);
+        }
+
+        if let Err(mut errs) = self.hyper.validate() {
+            errors.append(&mut errs);
+        }
+
+        if !errors.is_empty() {
+            return Err(HyperDarwinError::InvalidSettings(errors));
+        }
+
+        tasks::build(&self.task, &self.task_params).map(|_| ())
+    }
+
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        config::dump(self, ConfigFormat::Toml)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub best_fitness: f64, // Best fitness of this generation
+    pub mean_fitness: f64,
+    pub champion_fitness: f64, // Best fitness of the whole run
+    pub species: usize,
+    pub mean_nodes: f64,
+    pub mean_conns: f64,
+    pub solved: bool,
+}
+
+impl GenerationStats {
+    const CSV_HEADER: &'static str =
+        "generation,best_fitness,mean_fitness,champion_fitness,species,mean_nodes,mean_conns,solved";
+
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.generation,
+            self.best_fitness,
+            self.mean_fitness,
+            self.champion_fitness,
+            self.species,
+            self.mean_nodes,
+            self.mean_conns,
+            self.solved
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct CheckpointRef<'a> {
+    config: &'a ExperimentConfig,
+    solved: bool,
+    population: &'a Population,
+}
+
+#[derive(Deserialize)]
+struct Checkpoint {
+    config: ExperimentConfig,
+    solved: bool,
+    population: Population,
+}
+
+// A run of a built-in task. The output directory receives the effective
+// configuration, per-generation stats, the champion and checkpoints.
+pub struct Experiment {
+    config: ExperimentConfig,
+    task: Box<dyn Task>,
+    population: Population,
+    solved: bool,
+}
+
+impl Experiment {
+    pub fn new(mut config: ExperimentConfig) -> Result<Self, HyperDarwinError> {
+        let task = tasks::build(&config.task, &config.task_params)?;
+
+        config.settings.encoding = task.encoding();
+        config.validate()?;
+
+        // TOML integers are signed, so drawn seeds stay below 2^63
+        let seed = *config.seed.get_or_insert_with(|| random::<u64>() >> 1);
+        rng::seed(seed);
+
+        fs::create_dir_all(&config.output_dir)?;
+        fs::write(config.output_dir.join(CONFIG_FILE), config.to_toml()?)?;
+
+        let mut stats = File::create(config.output_dir.join(STATS_FILE))?;
+        writeln!(stats, "{}", GenerationStats::CSV_HEADER)?;
+
+        let sets = config.settings.clone();
+
+        Ok(Self {
+            config,
+            task,
+            population: Population::new(sets),
+            solved: false,
+        })
+    }
+
+    // Picks a run back up from a checkpoint, optionally with a new total
+    // number of generations. The generator is reseeded from the seed and the
+    // generation, so resumed runs are reproducible but differ from a run that
+    // was never interrupted.
+    pub fn resume<P: AsRef<Path>>(
+        checkpoint: P,
+        generations: Option<u64>,
+    ) -> Result<Self, HyperDarwinError> {
+        let reader = BufReader::new(File::open(checkpoint)?);
+        let Checkpoint {
+            mut config,
+            solved,
+            population,
+        } = serde_json::from_reader(reader)?;
+
+        if let Some(generations) = generations {
+            config.generations = generations;
+        }
+
+        let task = tasks::build(&config.task, &config.task_params)?;
+
+        rng::seed(
+            config
+                .seed
+                .unwrap_or(0)
+                .wrapping_add(population.generations),
+        );
+
+        Ok(Self {
+            config,
+            task,
+            population,
+            solved,
+        })
+    }
+
+    pub fn config(&self) -> &ExperimentConfig {
+        &self.config
+    }
+
+    pub fn population(&self) -> &Population {
+        &self.population
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    pub fn is_finished(&self) -> bool {
+        (self.solved && self.config.stop_when_solved)
+            || self.population.generations >= self.config.generations
+    }
+
+    // Evaluates the current generation, logs it and breeds the next one
+    pub fn step(&mut self) -> Result<GenerationStats, HyperDarwinError> {
+        let task = &self.task;
+        let hyper = &self.config.hyper;
+
+        for genome in self.population.get_citizens().iter_mut() {
+            let (fitness, behavior) = task.evaluate_with_behavior(genome, hyper);
+
+            genome.fitness = 0.;
+            genome.add_fitness(fitness);
+
+            if let Some(behavior) = behavior {
+                genome.set_behavior(behavior);
+            }
+        }
+
+        let citizens = self.population.citizens();
+        let len = citizens.len().max(1) as f64;
+
+        let best_fitness = citizens.iter().map(|g| g.fitness).fold(0., f64::max);
+        let mean_fitness = citizens.iter().map(|g| g.fitness).sum::<f64>() / len;
+        let mean_nodes = citizens.iter().map(|g| g.nodes.len()).sum::<usize>() as f64 / len;
+        let mean_conns = citizens.iter().map(|g| g.conns.len()).sum::<usize>() as f64 / len;
+        let generation = self.population.generations;
+        let previous_champion = self.population.best_fitness;
+
+        if let Some(best) = citizens
+            .iter()
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+        {
+            self.solved |= self.task.solved(best, hyper);
+        }
+
+        self.population.try_next_generation()?;
+
+        let stats = GenerationStats {
+            generation,
+            best_fitness,
+            mean_fitness,
+            champion_fitness: self.population.best_fitness,
+            species: self.population.species().len(),
+            mean_nodes,
+            mean_conns,
+            solved: self.solved,
+        };
+
+        let mut log = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(self.config.output_dir.join(STATS_FILE))?;
+        writeln!(log, "{}", stats.csv_row())?;
+
+        if self.population.best_fitness > previous_champion {
+            if let Some(champion) = &self.population.best_genome {
+                save_genome(champion, self.config.output_dir.join(CHAMPION_FILE))?;
+            }
+        }
+
+        let interval = self.config.checkpoint_interval;
+
+        if self.is_finished() || (interval > 0 && self.population.generations % interval == 0) {
+            self.checkpoint()?;
+        }
+
+        Ok(stats)
+    }
+
+    // Steps until the run is finished, returning the champion
+    pub fn run<F>(&mut self, mut on_generation: F) -> Result<Option<&Genome>, HyperDarwinError>
+    where
+        F: FnMut(&GenerationStats),
+    {
+        while !self.is_finished() {
+            let stats = self.step()?;
+            on_generation(&stats);
+        }
+
+        Ok(self.population.best_genome.as_ref())
+    }
+
+    pub fn checkpoint(&self) -> Result<(), HyperDarwinError> {
+        let path = self.config.output_dir.join(CHECKPOINT_FILE);
+        let writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer(
+            writer,
+            &CheckpointRef {
+                config: &self.config,
+                solved: self.solved,
+                population: &self.population,
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+pub fn save_genome<P: AsRef<Path>>(genome: &Genome, path: P) -> Result<(), HyperDarwinError> {
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(writer, genome)?;
+
+    Ok(())
+}
+
+pub fn load_genome<P: AsRef<Path>>(path: P) -> Result<Genome, HyperDarwinError> {
+    let reader = BufReader::new(File::open(path)?);
+
+    Ok(serde_json::from_reader(reader)?)
+}
@@ -0,0 +1,53 @@
+// JSON has no infinities or NaN and serde_json silently writes them as null,
+// which then fails to load. Infinities are stored as the largest finite values
+// and read back as infinities, NaN is refused.
+use serde::de::Deserializer;
+use serde::ser::{Error, Serializer};
+use serde::Deserialize;
+
+fn to_finite<E: Error>(val: f64) -> Result<f64, E> {
+    match val {
+        v if v.is_nan() => Err(E::custom("cannot serialize NaN")),
+        v if v == f64::INFINITY => Ok(f64::MAX),
+        v if v == f64::NEG_INFINITY => Ok(f64::MIN),
+        v => Ok(v),
+    }
+}
+
+fn from_finite(val: f64) -> f64 {
+    match val {
+        v if v == f64::MAX => f64::INFINITY,
+        v if v == f64::MIN => f64::NEG_INFINITY,
+        v => v,
+    }
+}
+
+pub fn serialize<S: Serializer>(val: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(to_finite(*val)?)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(from_finite(f64::deserialize(deserializer)?))
+}
+
+pub mod vec {
+    use serde::de::Deserializer;
+    use serde::ser::{SerializeSeq, Serializer};
+    use serde::Deserialize;
+
+    pub fn serialize<S: Serializer>(vals: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(vals.len()))?;
+
+        for &val in vals {
+            seq.serialize_element(&super::to_finite::<S::Error>(val)?)?;
+        }
+
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+        let vals = Vec::<f64>::deserialize(deserializer)?;
+
+        Ok(vals.into_iter().map(super::from_finite).collect())
+    }
+}
@@ -1,27 +1,74 @@
-use crate::activations::Activations;
+use crate::activations::ActivationSet;
 use crate::connection::Connection;
+use crate::error::HyperDarwinError;
 use crate::history::History;
 use crate::hyper_tensor::HyperTensor;
+use crate::lineage::Mutation;
 use crate::node::Node;
+use crate::rng::thread_rng;
 use crate::settings::HyperSettings;
 use crate::settings::Settings;
+use crate::substrate;
 
 use rand::prelude::*;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use serde::{de, Deserialize, Deserializer, Serialize};
 
 use std::clone::Clone;
 use std::collections::HashMap;
 use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::vec::Vec;
 
+static NEXT_GENOME_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_genome_id() -> u64 {
+    NEXT_GENOME_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// Loaded Genomes keep their ids, so new ones have to be numbered after them.
+// The last id is refused, there would be none left.
+fn reserve_genome_id<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let id = u64::deserialize(deserializer)?;
+    let next = id
+        .checked_add(1)
+        .ok_or_else(|| de::Error::custom(format!("genome id {} leaves no id to give", id)))?;
+
+    NEXT_GENOME_ID.fetch_max(next, Ordering::Relaxed);
+
+    Ok(id)
+}
+
 // Main Genome Class
+#[derive(Serialize, Deserialize)]
 pub struct Genome {
-    inputs: u32,                // Number of Inputs
-    outputs: u32,               // Number of Outputs
-    pub nodes: Vec<Node>,       // Vector of Nodes
-    pub conns: Vec<Connection>, // Vector of Connections
-    pub fitness: f64,           // Fitness of this Genome
+    #[serde(deserialize_with = "reserve_genome_id")]
+    pub id: u64, // Unique identity of this Genome
+    pub parents: Vec<u64>,         // Ids of the parents, fitter parent first
+    pub birth_generation: u64,     // Generation this Genome was born in
+    pub species_id: Option<usize>, // Species this Genome was last placed in
+    pub mutations: Vec<Mutation>,  // Mutations applied since birth
+    inputs: u32,                   // Number of Inputs
+    outputs: u32,                  // Number of Outputs
+    pub nodes: Vec<Node>,          // Vector of Nodes
+    pub conns: Vec<Connection>,    // Vector of Connections
+    #[serde(with = "crate::finite")]
+    pub fitness: f64, // Fitness of this Genome
+    #[serde(with = "crate::finite")]
+    pub objective_fitness: f64, // Fitness before novelty, ranking or sharing replaced it
+    #[serde(with = "crate::finite::vec")]
+    pub behavior: Vec<f64>, // Behavior descriptor for novelty search
+    #[serde(with = "crate::finite")]
+    pub novelty: f64, // Novelty score from the last generation
+    #[serde(with = "crate::finite::vec")]
+    pub objectives: Vec<f64>, // Objective values for multi-objective evolution
+    pub pareto_rank: usize,        // Non-dominated front this Genome was placed in
+    #[serde(with = "crate::finite")]
+    pub crowding_distance: f64, // Crowding distance within its front
 }
 
 impl fmt::Debug for Genome {
@@ -46,12 +93,41 @@ impl fmt::Debug for Genome {
 
 impl Genome {
     pub fn new(inputs: u32, outputs: u32, crossover: bool) -> Self {
+        Self::with_activations(inputs, outputs, crossover, &ActivationSet::Cppn)
+    }
+
+    pub fn from_settings(sets: &Settings) -> Self {
+        Self::with_activations(
+            sets.genome_inputs(),
+            sets.genome_outputs(),
+            false,
+            &sets.activations,
+        )
+    }
+
+    pub fn with_activations(
+        inputs: u32,
+        outputs: u32,
+        crossover: bool,
+        activations: &ActivationSet,
+    ) -> Self {
         let mut genome = Self {
+            id: next_genome_id(),
+            parents: Vec::new(),
+            birth_generation: 0,
+            species_id: None,
+            mutations: Vec::new(),
             inputs,
             outputs,
             nodes: Vec::with_capacity((inputs + outputs + 1) as usize),
             conns: Vec::with_capacity(((inputs + 1) * outputs) as usize),
             fitness: 0.,
+            objective_fitness: 0.,
+            behavior: Vec::new(),
+            novelty: 0.,
+            objectives: Vec::new(),
+            pareto_rank: 0,
+            crowding_distance: 0.,
         };
 
         if crossover {
@@ -66,7 +142,7 @@ impl Genome {
         for i in 1..=(inputs + 1) {
             genome
                 .nodes
-                .push(Node::new(i, 0., dy_curr, rng.gen::<Activations>()));
+                .push(Node::new(i, 0., dy_curr, activations.sample(&mut rng)));
             dy_curr += dy;
         }
 
@@ -76,7 +152,7 @@ impl Genome {
         for i in (inputs + 2)..(inputs + outputs + 2) {
             genome
                 .nodes
-                .push(Node::new(i, 1., dy_curr, rng.gen::<Activations>()));
+                .push(Node::new(i, 1., dy_curr, activations.sample(&mut rng)));
             dy_curr += dy;
         }
 
@@ -96,36 +172,116 @@ impl Genome {
         genome
     }
 
+    pub fn inputs(&self) -> u32 {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> u32 {
+        self.outputs
+    }
+
+    pub fn bias_node(&self) -> u32 {
+        self.inputs + 1
+    }
+
+    pub fn distance(&self, other: &Self, sets: &Settings) -> f64 {
+        sets.compatibility.distance(self, other, sets)
+    }
+
     pub fn add_fitness(&mut self, fit: f64) {
         let fitness = self.fitness + fit;
 
         self.fitness = if fitness < 0. { 0. } else { fitness };
     }
 
-    pub fn feed_forward(&self, input: &Vec<f64>) -> Result<f64, &'static str> {
+    pub fn set_behavior(&mut self, behavior: Vec<f64>) {
+        self.behavior = behavior;
+    }
+
+    pub fn set_objectives(&mut self, objectives: Vec<f64>) {
+        self.objectives = objectives;
+    }
+
+    pub(crate) fn clear_evaluation(&mut self) {
+        self.fitness = 0.;
+        self.objective_fitness = 0.;
+        self.novelty = 0.;
+        self.behavior.clear();
+        self.objectives.clear();
+        self.pareto_rank = 0;
+        self.crowding_distance = 0.;
+    }
+
+    // Value of the first output node
+    pub fn feed_forward(&self, input: &[f64]) -> Result<f64, HyperDarwinError> {
+        self.feed_forward_all(input)?
+            .first()
+            .copied()
+            .ok_or(HyperDarwinError::NoOutputs)
+    }
+
+    // Values of every output node, in order. Recurrent connections carry
+    // nothing, use `activate` to feed a sequence through them.
+    pub fn feed_forward_all(&self, input: &[f64]) -> Result<Vec<f64>, HyperDarwinError> {
+        self.activate(input, &mut HashMap::new())
+    }
+
+    // One step of a sequence. A connection is recurrent when it doesn't lead
+    // further right, and carries what its source node output on the previous
+    // step. `memory` keeps those outputs between steps and starts out empty.
+    pub fn activate(
+        &self,
+        input: &[f64],
+        memory: &mut HashMap<u32, f64>,
+    ) -> Result<Vec<f64>, HyperDarwinError> {
         if input.len() != self.inputs as usize {
-            return Err("Provided input size doesn't match Genome input size");
+            return Err(HyperDarwinError::InputSizeMismatch {
+                expected: self.inputs as usize,
+                actual: input.len(),
+            });
         }
 
+        let node_x = self
+            .nodes
+            .iter()
+            .map(|n| (n.innov, n.x))
+            .collect::<HashMap<u32, f64>>();
+
+        let recurrent = |conn: &Connection| match (node_x.get(&conn.from), node_x.get(&conn.to)) {
+            (Some(from), Some(to)) => from >= to,
+            _ => false,
+        };
+
         let mut node_vals = HashMap::<u32, f64>::new();
 
-        let mut i = 1;
-        for val in input {
+        for (i, val) in (1..).zip(input.iter()) {
             node_vals.insert(i, *val);
-            i += 1;
         }
 
         node_vals.insert(self.inputs + 1, 1.);
 
+        for conn in self.conns.iter().filter(|c| recurrent(c)) {
+            let to_val = node_vals.entry(conn.to).or_insert(0.);
+            if !conn.enabled {
+                continue;
+            }
+            *to_val += memory.get(&conn.from).copied().unwrap_or(0.) * conn.weight;
+        }
+
         for node in self.nodes.iter() {
             let from_val = match node_vals.get(&node.innov) {
                 Some(v) => *v,
-                None => return Err("No val"),
+                None => return Err(HyperDarwinError::UnreachableNode(node.innov)),
             };
 
             let feed_forward_val = node.activate(from_val);
+            memory.insert(node.innov, feed_forward_val);
 
-            for conn in self.conns.iter().filter(|&c| c.from == node.innov) {
+            for conn in self
+                .conns
+                .iter()
+                .filter(|&c| c.from == node.innov && !recurrent(c))
+            {
                 let to_val = node_vals.entry(conn.to).or_insert(0.);
                 if !conn.enabled {
                     continue;
@@ -134,93 +290,61 @@ impl Genome {
             }
         }
 
-        Ok(((1. / (1. + (*node_vals.get(&(self.inputs + 2)).unwrap() * -4.9).exp())) - 0.5) * 2.)
+        Ok(((self.inputs + 2)..(self.inputs + self.outputs + 2))
+            .map(|out| {
+                let val = node_vals.get(&out).copied().unwrap_or(0.);
+                ((1. / (1. + (val * -4.9).exp())) - 0.5) * 2.
+            })
+            .collect())
     }
 
+    // Streams the substrate weights from the CPPN. Decode a Substrate instead to
+    // activate the same weights repeatedly.
     pub fn hyper_feed_forward<T>(
         &self,
         input: HyperTensor,
         sets: &HyperSettings,
         third_param_fn: Option<T>,
-    ) -> Result<Vec<Vec<f64>>, &'static str>
+    ) -> Result<Vec<Vec<f64>>, HyperDarwinError>
     where
         T: Fn(f64, f64) -> f64,
     {
-        if self.inputs == 4 && third_param_fn.is_some() {
-            return Err("Didn't expect a Third Parameter Function");
-        } else if self.inputs == 6 && third_param_fn.is_none() {
-            return Err("Expected a Third Parameter Function. Got none");
-        }
-
-        let dy = (input.m - 1.) / 2.;
-        let dx = (input.n - 1.) / 2.;
-
-        let x_dir = (0..input.n as u64)
-            .map(|v| -1.0 + v as f64 * dx)
-            .collect::<Vec<f64>>();
-
-        let y_dir = (0..input.m as u64)
-            .map(|v| -1.0 + v as f64 * dy)
-            .collect::<Vec<f64>>();
-
-        let mut res = HyperTensor::zeros(input.m as usize, input.n as usize).unwrap();
-
-        if self.inputs == 6 {
-            let tpfn = third_param_fn.unwrap();
-
-            for (&y1, row_inp) in y_dir.iter().zip(input.values.iter()) {
-                for (&x1, val_inp) in x_dir.iter().zip(row_inp.iter()) {
-                    for (&y2, row_out) in y_dir.iter().zip(res.values.iter_mut()) {
-                        for (&x2, val_out) in x_dir.iter().zip(row_out.iter_mut()) {
-                            let inp = vec![x1, y1, tpfn(x1, y1), x2, y2, tpfn(x2, y2)];
-
-                            let cppn_out = self.feed_forward(&inp).unwrap();
-
-                            *val_out += sets.scaled_weight(cppn_out) * val_inp;
-                        }
-                    }
-                }
-            }
-        } else {
-            for (&y1, row_inp) in y_dir.iter().zip(input.values.iter()) {
-                for (&x1, val_inp) in x_dir.iter().zip(row_inp.iter()) {
-                    for (&y2, row_out) in y_dir.iter().zip(res.values.iter_mut()) {
-                        for (&x2, val_out) in x_dir.iter().zip(row_out.iter_mut()) {
-                            let inp = vec![x1, y1, x2, y2];
-
-                            let cppn_out = self.feed_forward(&inp).unwrap();
-
-                            *val_out += sets.scaled_weight(cppn_out) * val_inp;
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(res.values)
+        substrate::activate_once(self, &input, sets, third_param_fn)
     }
 
     pub fn mutate(&mut self, hist: &mut History, sets: &Settings) {
         let mut rng = thread_rng();
 
+        let mut mutated_weights = 0;
+
         self.conns.iter_mut().for_each(|c| {
             if rng.gen::<f64>() < sets.wt_mut_rate {
                 c.mutate_weight(sets);
+                mutated_weights += 1;
             }
         });
 
+        if mutated_weights > 0 {
+            self.mutations.push(Mutation::Weights(mutated_weights));
+        }
+
+        // A structural mutation that can't be applied simply doesn't happen
         if rng.gen::<f64>() < sets.conn_mut_rate {
-            self.add_conn(hist);
+            if sets.recurrent_conn_rate > 0. && rng.gen::<f64>() < sets.recurrent_conn_rate {
+                self.add_recurrent_conn(hist).ok();
+            } else {
+                self.add_conn(hist).ok();
+            }
         }
 
         if rng.gen::<f64>() < sets.node_mut_rate {
-            self.add_node(hist);
+            self.add_node(hist, sets).ok();
         }
 
-        self.conns.sort_unstable_by(|a, b| a.innov.cmp(&b.innov));
+        self.conns.sort_unstable_by_key(|c| c.innov);
     }
 
-    fn add_conn(&mut self, hist: &mut History) {
+    pub fn add_conn(&mut self, hist: &mut History) -> Result<(), HyperDarwinError> {
         let mut rng = thread_rng();
 
         let from_node_pool = self
@@ -244,15 +368,14 @@ impl Genome {
                     })
                     .collect::<Vec<&Node>>();
 
-                to_nodes.len() > 0
+                !to_nodes.is_empty()
             })
             .collect::<Vec<&Node>>();
 
-        if from_node_pool.len() == 0 {
-            return;
-        }
-
-        let from_node = from_node_pool.choose(&mut rng).unwrap();
+        let from_node = match from_node_pool.choose(&mut rng) {
+            Some(node) => node,
+            None => return Err(HyperDarwinError::NoConnectionAvailable),
+        };
 
         let to_node_pool = self
             .nodes
@@ -269,7 +392,10 @@ impl Genome {
             })
             .collect::<Vec<&Node>>();
 
-        let to_node = to_node_pool.choose(&mut rng).unwrap();
+        let to_node = match to_node_pool.choose(&mut rng) {
+            Some(node) => node,
+            None => return Err(HyperDarwinError::NoConnectionAvailable),
+        };
 
         let innov = hist.mutate_conn(from_node, to_node);
 
@@ -281,31 +407,101 @@ impl Genome {
             true,
         );
 
+        self.mutations.push(Mutation::AddConnection {
+            innov,
+            from: new_conn.from,
+            to: new_conn.to,
+        });
         self.conns.push(new_conn);
+
+        Ok(())
     }
 
-    fn add_node(&mut self, hist: &mut History) {
+    // Adds a connection leading back to a node at or left of its source, a
+    // node's connection to itself included. Inputs and the bias can't be its
+    // target.
+    pub fn add_recurrent_conn(&mut self, hist: &mut History) -> Result<(), HyperDarwinError> {
         let mut rng = thread_rng();
 
-        let conn_to_mutate = self.conns.iter_mut().choose(&mut rng).unwrap();
-
-        let details = hist.mutate_node(&conn_to_mutate);
-
-        let from_node = self
-            .nodes
-            .iter()
-            .find(|n| n.innov == conn_to_mutate.from)
-            .unwrap();
-        let to_node = self
+        let candidates = self
             .nodes
             .iter()
-            .find(|n| n.innov == conn_to_mutate.to)
-            .unwrap();
+            .flat_map(|from| {
+                self.nodes
+                    .iter()
+                    .filter(move |to| to.x > 0. && to.x <= from.x)
+                    .map(move |to| (from, to))
+            })
+            .filter(|(from, to)| {
+                !self
+                    .conns
+                    .iter()
+                    .any(|c| c.from == from.innov && c.to == to.innov)
+            })
+            .collect::<Vec<(&Node, &Node)>>();
+
+        let (from_node, to_node) = match candidates.choose(&mut rng) {
+            Some(&pair) => pair,
+            None => return Err(HyperDarwinError::NoConnectionAvailable),
+        };
+
+        let innov = hist.mutate_conn(from_node, to_node);
+
+        let new_conn = Connection::new(
+            innov,
+            from_node.innov,
+            to_node.innov,
+            rng.gen::<f64>(),
+            true,
+        );
+
+        self.mutations.push(Mutation::AddConnection {
+            innov,
+            from: new_conn.from,
+            to: new_conn.to,
+        });
+        self.conns.push(new_conn);
+
+        Ok(())
+    }
+
+    // Splits a random connection. Does nothing if the Genome already has the
+    // node that splitting it would create.
+    pub fn add_node(
+        &mut self,
+        hist: &mut History,
+        sets: &Settings,
+    ) -> Result<(), HyperDarwinError> {
+        let mut rng = thread_rng();
+
+        let conn_idx = match (0..self.conns.len()).choose(&mut rng) {
+            Some(idx) => idx,
+            None => return Err(HyperDarwinError::NoConnections),
+        };
+
+        let find_node = |innov: u32| {
+            self.nodes
+                .iter()
+                .find(|n| n.innov == innov)
+                .cloned()
+                .ok_or(HyperDarwinError::MissingNode(innov))
+        };
+
+        let from_node = find_node(self.conns[conn_idx].from)?;
+        let to_node = find_node(self.conns[conn_idx].to)?;
+
+        let conn_to_mutate = &mut self.conns[conn_idx];
+
+        let details = hist.mutate_node(conn_to_mutate);
+
+        if self.nodes.iter().any(|n| n.innov == details.node) {
+            return Ok(());
+        }
 
         let x = (from_node.x + to_node.x) / 2.;
         let y = (from_node.y + to_node.y) / 2.;
 
-        let new_node = Node::new(details.node, x, y, rand::random::<Activations>());
+        let new_node = Node::new(details.node, x, y, sets.activations.sample(&mut rng));
         let in_conn = Connection::new(details.in_conn, from_node.innov, new_node.innov, 1., true);
 
         let out_conn = Connection::new(
@@ -317,12 +513,17 @@ impl Genome {
         );
 
         conn_to_mutate.disable();
+        self.mutations.push(Mutation::AddNode {
+            node: new_node.innov,
+            split: conn_to_mutate.innov,
+        });
         self.nodes.push(new_node);
         self.conns.push(in_conn);
         self.conns.push(out_conn);
 
-        self.nodes
-            .sort_unstable_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+        self.nodes.sort_unstable_by(|a, b| a.x.total_cmp(&b.x));
+
+        Ok(())
     }
 
     pub fn crossover(parent1: &Self, parent2: &Self, sets: &Settings) -> Self {
@@ -343,8 +544,7 @@ impl Genome {
         });
 
         for conn in &male.conns {
-            if f_genes.contains_key(&conn.innov) {
-                let f_gene = *f_genes.get(&conn.innov).unwrap();
+            if let Some(&f_gene) = f_genes.get(&conn.innov) {
                 let mut gene = if rng.gen::<f64>() < 0.5 {
                     f_gene.clone()
                 } else {
@@ -377,19 +577,89 @@ impl Genome {
         let mut offspring = Self::new(male.inputs, male.outputs, true);
         offspring.conns = offspring_genes;
         offspring.nodes = male.nodes.clone();
+        offspring.parents = vec![male.id, female.id];
 
         offspring
     }
+
+    // The network in Graphviz DOT, inputs on the left and outputs on the right.
+    // Disabled connections are dashed, negative weights are red.
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "digraph genome_{} {{", self.id)?;
+        writeln!(writer, "  rankdir=LR;")?;
+
+        for node in &self.nodes {
+            let (label, shape) = if node.innov == self.bias_node() {
+                (String::from("bias"), "box")
+            } else if node.innov <= self.inputs {
+                (format!("in {}", node.innov), "box")
+            } else if node.innov < self.inputs + self.outputs + 2 {
+                (
+                    format!(
+                        "out {}\\n{:?}",
+                        node.innov - self.inputs - 1,
+                        node.activation
+                    ),
+                    "doublecircle",
+                )
+            } else {
+                (format!("{}\\n{:?}", node.innov, node.activation), "circle")
+            };
+
+            writeln!(
+                writer,
+                "  n{} [label=\"{}\", shape={}];",
+                node.innov, label, shape
+            )?;
+        }
+
+        for conn in &self.conns {
+            writeln!(
+                writer,
+                "  n{} -> n{} [label=\"{:.2}\", color={}, style={}, penwidth={:.2}];",
+                conn.from,
+                conn.to,
+                conn.weight,
+                if conn.weight < 0. { "red" } else { "black" },
+                if conn.enabled { "solid" } else { "dashed" },
+                0.5 + conn.weight.abs().min(5.)
+            )?;
+        }
+
+        writeln!(writer, "}}")
+    }
+
+    // An asexual child: same genes, fresh identity
+    pub fn offspring(&self) -> Self {
+        let mut child = self.clone();
+        child.id = next_genome_id();
+        child.parents = vec![self.id];
+        child.mutations.clear();
+        child.species_id = None;
+
+        child
+    }
 }
 
 impl Clone for Genome {
     fn clone(&self) -> Self {
         Self {
+            id: self.id,
+            parents: self.parents.clone(),
+            birth_generation: self.birth_generation,
+            species_id: self.species_id,
+            mutations: self.mutations.clone(),
             inputs: self.inputs,
             outputs: self.outputs,
             nodes: self.nodes.clone(),
             conns: self.conns.clone(),
             fitness: self.fitness,
+            objective_fitness: self.objective_fitness,
+            behavior: self.behavior.clone(),
+            novelty: self.novelty,
+            objectives: self.objectives.clone(),
+            pareto_rank: self.pareto_rank,
+            crowding_distance: self.crowding_distance,
         }
     }
 }
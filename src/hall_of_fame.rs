@@ -0,0 +1,100 @@
+use crate::evolvable::Evolvable;
+use crate::genome::Genome;
+
+use serde::{Deserialize, Serialize};
+
+use std::vec::Vec;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HallOfFameEntry<G = Genome> {
+    pub genome: G,
+    pub generation: u64,
+    pub species_id: usize,
+    #[serde(with = "crate::finite")]
+    pub fitness: f64,
+}
+
+impl<G: Evolvable> HallOfFameEntry<G> {
+    pub fn new(genome: &G, generation: u64, species_id: usize) -> Self {
+        Self {
+            genome: genome.clone(),
+            generation,
+            species_id,
+            fitness: genome.objective_fitness(),
+        }
+    }
+}
+
+// The top `capacity` genetically distinct Genomes seen so far, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HallOfFame<G = Genome> {
+    capacity: usize,
+    entries: Vec<HallOfFameEntry<G>>,
+}
+
+impl<G: Evolvable> HallOfFame<G> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn entries(&self) -> &[HallOfFameEntry<G>] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> Option<&HallOfFameEntry<G>> {
+        self.entries.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Returns whether the entry made it in
+    pub fn consider(&mut self, entry: HallOfFameEntry<G>) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        if let Some(pos) = self
+            .entries
+            .iter()
+            .position(|e| e.genome.same_genes(&entry.genome))
+        {
+            if self.entries[pos].fitness >= entry.fitness {
+                return false;
+            }
+
+            self.entries.remove(pos);
+        } else if self.entries.len() >= self.capacity
+            && self.entries[self.entries.len() - 1].fitness >= entry.fitness
+        {
+            return false;
+        }
+
+        let pos = self
+            .entries
+            .iter()
+            .position(|e| e.fitness < entry.fitness)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(pos, entry);
+        self.entries.truncate(self.capacity);
+
+        true
+    }
+}
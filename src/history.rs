@@ -1,32 +1,28 @@
 use crate::connection::Connection;
+use crate::genome::Genome;
 use crate::node::Node;
 
-use std::vec::Vec;
+use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize)]
 pub struct History {
-    pub conn_history: Vec<HistConnection>,
+    #[serde(with = "pairs")]
+    conn_innovs: HashMap<(u32, u32), u32>, // (from, to) -> connection innovation
+    #[serde(with = "pairs")]
+    node_splits: HashMap<(u32, u32), NodeMut>, // (from, to) of the split connection -> new genes
     next_node_innov: u32,
     next_conn_innov: u32,
 }
 
-pub struct HistConnection {
-    innov: u32,
-    from: u32,
-    to: u32,
-}
-
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct NodeMut {
     pub node: u32,
     pub in_conn: u32,
     pub out_conn: u32,
 }
 
-impl HistConnection {
-    fn new(innov: u32, from: u32, to: u32) -> Self {
-        Self { innov, from, to }
-    }
-}
-
 impl NodeMut {
     fn new(node: u32, in_conn: u32, out_conn: u32) -> Self {
         Self {
@@ -40,7 +36,8 @@ impl NodeMut {
 impl History {
     pub fn new(inputs: u32, outputs: u32) -> Self {
         let mut hist = Self {
-            conn_history: Vec::with_capacity(((inputs + 1) * outputs + 1) as usize),
+            conn_innovs: HashMap::with_capacity(((inputs + 1) * outputs) as usize),
+            node_splits: HashMap::new(),
             next_node_innov: inputs + outputs + 2,
             next_conn_innov: (inputs + 1) * outputs + 1,
         };
@@ -48,7 +45,7 @@ impl History {
         let mut innov = 1;
         for inp in 1..=(inputs + 1) {
             for out in (inputs + 2)..(inputs + outputs + 2) {
-                hist.conn_history.push(HistConnection::new(innov, inp, out));
+                hist.conn_innovs.insert((inp, out), innov);
                 innov += 1;
             }
         }
@@ -56,64 +53,85 @@ impl History {
         hist
     }
 
+    // Forgets which structural mutations have been seen while keeping the
+    // innovation counters, so that the same mutation only shares an innovation
+    // number with others from the same generation
+    pub fn reset_tracking(&mut self) {
+        self.conn_innovs.clear();
+        self.node_splits.clear();
+    }
+
+    // Takes in the genes of a Genome evolved under another History, so new
+    // mutations reuse its innovations instead of clashing with them
+    pub fn include(&mut self, genome: &Genome) {
+        for conn in &genome.conns {
+            self.conn_innovs.insert((conn.from, conn.to), conn.innov);
+            self.next_conn_innov = self.next_conn_innov.max(conn.innov + 1);
+        }
+
+        for node in &genome.nodes {
+            self.next_node_innov = self.next_node_innov.max(node.innov + 1);
+        }
+    }
+
     pub fn mutate_conn(&mut self, from: &Node, to: &Node) -> u32 {
-        match self
-            .conn_history
-            .iter()
-            .find(|c| c.from == from.innov && c.to == to.innov)
-        {
-            Some(conn) => conn.innov,
-            None => {
-                let res = self.next_conn_innov;
-                self.next_conn_innov += 1;
-                self.conn_history
-                    .push(HistConnection::new(res, from.innov, to.innov));
+        let next_conn_innov = &mut self.next_conn_innov;
+
+        *self
+            .conn_innovs
+            .entry((from.innov, to.innov))
+            .or_insert_with(|| {
+                let res = *next_conn_innov;
+                *next_conn_innov += 1;
                 res
-            }
-        }
+            })
     }
 
     pub fn mutate_node(&mut self, conn: &Connection) -> NodeMut {
-        let from_conns = self
-            .conn_history
-            .iter()
-            .filter(|c| c.from == conn.from)
-            .collect::<Vec<&HistConnection>>();
-        let to_conns = self
-            .conn_history
-            .iter()
-            .filter(|c| c.to == conn.to)
-            .collect::<Vec<&HistConnection>>();
-
-        let mut res: Option<NodeMut> = None;
-
-        'outer: for from_conn in from_conns {
-            for to_conn in &to_conns {
-                if from_conn.to == to_conn.from {
-                    res = Some(NodeMut::new(from_conn.to, from_conn.innov, to_conn.innov));
-                    break 'outer;
-                }
-            }
+        if let Some(details) = self.node_splits.get(&(conn.from, conn.to)) {
+            return *details;
         }
 
-        match res {
-            Some(innov) => innov,
-            None => {
-                let new_node_innov = self.next_node_innov;
-                self.next_node_innov += 1;
-                let new_in_innov = self.next_conn_innov;
-                self.next_conn_innov += 1;
-                let new_in = HistConnection::new(new_in_innov, conn.from, new_node_innov);
+        let new_node_innov = self.next_node_innov;
+        self.next_node_innov += 1;
+        let new_in_innov = self.next_conn_innov;
+        self.next_conn_innov += 1;
+        let new_out_innov = self.next_conn_innov;
+        self.next_conn_innov += 1;
 
-                let new_out_innov = self.next_conn_innov;
-                self.next_conn_innov += 1;
-                let new_out = HistConnection::new(new_out_innov, new_node_innov, conn.to);
+        self.conn_innovs
+            .insert((conn.from, new_node_innov), new_in_innov);
+        self.conn_innovs
+            .insert((new_node_innov, conn.to), new_out_innov);
 
-                self.conn_history.push(new_in);
-                self.conn_history.push(new_out);
+        let details = NodeMut::new(new_node_innov, new_in_innov, new_out_innov);
+        self.node_splits.insert((conn.from, conn.to), details);
 
-                NodeMut::new(new_node_innov, new_in_innov, new_out_innov)
-            }
-        }
+        details
+    }
+}
+
+// JSON only has string keys, so the maps are stored as lists of pairs
+mod pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use std::collections::HashMap;
+
+    pub fn serialize<V, S>(map: &HashMap<(u32, u32), V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, V, D>(deserializer: D) -> Result<HashMap<(u32, u32), V>, D::Error>
+    where
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<((u32, u32), V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
     }
 }
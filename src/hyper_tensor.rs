@@ -1,3 +1,5 @@
+use crate::error::HyperDarwinError;
+
 use std::vec::Vec;
 
 pub struct HyperTensor {
@@ -7,19 +9,20 @@ pub struct HyperTensor {
 }
 
 impl HyperTensor {
-    pub fn new(vec: Vec<Vec<f64>>) -> Result<Self, &'static str> {
-        if vec.len() < 2 {
-            return Err("Vector has too few rows");
-        }
-
-        let cols = vec[0].len();
+    pub fn new(vec: Vec<Vec<f64>>) -> Result<Self, HyperDarwinError> {
+        let rows = vec.len();
+        let cols = vec.first().map_or(0, |row| row.len());
 
-        if !vec.iter().all(|row| row.len() == cols) {
-            return Err("Row size is not consistent");
+        if rows < 2 || cols < 2 {
+            return Err(HyperDarwinError::InvalidTensorShape { rows, cols });
         }
 
-        if cols < 2 {
-            return Err("Vector has too few columns");
+        if let Some((row, values)) = vec.iter().enumerate().find(|(_, r)| r.len() != cols) {
+            return Err(HyperDarwinError::RaggedTensor {
+                row,
+                expected: cols,
+                actual: values.len(),
+            });
         }
 
         let m = vec.len() as f64;
@@ -28,9 +31,9 @@ impl HyperTensor {
         Ok(Self { values: vec, m, n })
     }
 
-    pub fn zeros(m: usize, n: usize) -> Result<Self, &'static str> {
+    pub fn zeros(m: usize, n: usize) -> Result<Self, HyperDarwinError> {
         if n < 2 || m < 2 {
-            return Err("Both dimensions must be at least 2 in size");
+            return Err(HyperDarwinError::InvalidTensorShape { rows: m, cols: n });
         }
 
         Self::new(vec![vec![0.0; n]; m])
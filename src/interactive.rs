@@ -0,0 +1,224 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::history::History;
+use crate::lineage::Genealogy;
+use crate::rng::thread_rng;
+use crate::settings::Settings;
+
+use rand::seq::{index, SliceRandom};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::vec::Vec;
+
+// Evolution steered by a person instead of a fitness function, as in
+// Picbreeder. The caller shows the current generation, marks the Genomes
+// they like and asks for the next generation, which keeps the selected
+// parents and fills the rest of its `pop_size` with their mutated offspring,
+// crossing two of them over unless `only_mut_rate` says otherwise.
+//
+// `branch` restarts from any Genome, e.g. one saved with `save_genome` in an
+// earlier session, so a promising ancestor can be explored in a new direction.
+// Selected Genomes score a fitness of 1 and the rest 0.
+#[derive(Serialize, Deserialize)]
+pub struct InteractiveEvolution {
+    sets: Settings,
+    hist: History,
+    generation: Vec<Genome>,
+    selected: Vec<usize>,
+    genealogy: Genealogy,
+    pub generations: u64,
+}
+
+impl InteractiveEvolution {
+    // A first generation of `pop_size` random Genomes
+    pub fn new(sets: Settings) -> Self {
+        let generation = (0..sets.pop_size)
+            .map(|_| Genome::from_settings(&sets))
+            .collect();
+
+        Self {
+            hist: History::new(sets.genome_inputs(), sets.genome_outputs()),
+            sets,
+            generation,
+            selected: Vec::new(),
+            genealogy: Genealogy::new(),
+            generations: 0,
+        }
+    }
+
+    // A session starting from the offspring of a single Genome
+    pub fn from_genome(sets: Settings, genome: &Genome) -> Result<Self, HyperDarwinError> {
+        let mut session = Self::new(sets);
+        session.branch(genome)?;
+
+        Ok(session)
+    }
+
+    // Writes the whole session, selection included, as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), HyperDarwinError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, HyperDarwinError> {
+        let reader = BufReader::new(File::open(path)?);
+
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.sets
+    }
+
+    pub fn genomes(&self) -> &[Genome] {
+        &self.generation
+    }
+
+    pub fn genome(&self, index: usize) -> Option<&Genome> {
+        self.generation.get(index)
+    }
+
+    pub fn genealogy(&self) -> &Genealogy {
+        &self.genealogy
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), HyperDarwinError> {
+        if index < self.generation.len() {
+            Ok(())
+        } else {
+            Err(HyperDarwinError::SelectionOutOfRange {
+                index,
+                len: self.generation.len(),
+            })
+        }
+    }
+
+    pub fn select(&mut self, index: usize) -> Result<(), HyperDarwinError> {
+        self.check_index(index)?;
+
+        if !self.selected.contains(&index) {
+            self.selected.push(index);
+        }
+
+        Ok(())
+    }
+
+    pub fn deselect(&mut self, index: usize) {
+        self.selected.retain(|&i| i != index);
+    }
+
+    // Replaces the selection with `indices`, leaving it untouched if any is
+    // out of range
+    pub fn select_only(&mut self, indices: &[usize]) -> Result<(), HyperDarwinError> {
+        for &index in indices {
+            self.check_index(index)?;
+        }
+
+        self.selected.clear();
+        indices.iter().for_each(|&index| {
+            if !self.selected.contains(&index) {
+                self.selected.push(index);
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+    }
+
+    // Indices of the selected Genomes, in the order they were selected
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    // Breeds the next generation from the selected Genomes, which carry over
+    // unchanged in front of their offspring
+    pub fn next_generation(&mut self) -> Result<(), HyperDarwinError> {
+        if self.selected.is_empty() {
+            return Err(HyperDarwinError::NothingSelected);
+        }
+
+        for (i, genome) in self.generation.iter_mut().enumerate() {
+            genome.fitness = if self.selected.contains(&i) { 1. } else { 0. };
+        }
+
+        let parents = self
+            .selected
+            .iter()
+            .map(|&i| self.generation[i].clone())
+            .collect::<Vec<Genome>>();
+
+        if self.sets.track_lineage {
+            for parent in &parents {
+                self.genealogy.record(parent, self.generations);
+            }
+        }
+
+        self.breed(&parents);
+        Ok(())
+    }
+
+    // Starts over from `genome`, which must have the inputs and outputs the
+    // Settings ask for. The new generation is the Genome and its mutated
+    // offspring, nothing else carries over.
+    pub fn branch(&mut self, genome: &Genome) -> Result<(), HyperDarwinError> {
+        let expected = (self.sets.genome_inputs(), self.sets.genome_outputs());
+        let actual = (genome.inputs(), genome.outputs());
+
+        if expected != actual {
+            return Err(HyperDarwinError::IncompatibleGenome { expected, actual });
+        }
+
+        self.hist = History::new(expected.0, expected.1);
+        self.hist.include(genome);
+
+        let mut root = genome.clone();
+        root.fitness = 1.;
+
+        self.breed(&[root]);
+        Ok(())
+    }
+
+    fn breed(&mut self, parents: &[Genome]) {
+        let mut rng = thread_rng();
+        let pop_size = (self.sets.pop_size as usize).max(parents.len());
+        let mut progeny = Vec::with_capacity(pop_size);
+
+        for parent in parents {
+            let mut parent = parent.clone();
+            parent.clear_evaluation();
+            progeny.push(parent);
+        }
+
+        while progeny.len() < pop_size {
+            let mut child = if parents.len() < 2 || rng.gen::<f64>() < self.sets.only_mut_rate {
+                parents.choose(&mut rng).unwrap().offspring()
+            } else {
+                let pair = index::sample(&mut rng, parents.len(), 2);
+
+                Genome::crossover(&parents[pair.index(0)], &parents[pair.index(1)], &self.sets)
+            };
+
+            child.mutate(&mut self.hist, &self.sets);
+            child.clear_evaluation();
+            child.birth_generation = self.generations + 1;
+            progeny.push(child);
+        }
+
+        self.generation = progeny;
+        self.selected.clear();
+        self.generations += 1;
+    }
+}
@@ -1,14 +1,66 @@
 mod activations;
+mod archipelago;
+mod clustering;
+mod coevolution;
+mod config;
 mod connection;
+mod distance;
+mod environment;
+mod error;
+mod evolvable;
+mod experiment;
+mod finite;
 mod genome;
+mod hall_of_fame;
 mod history;
 mod hyper_tensor;
+mod interactive;
+mod lineage;
+mod map_elites;
 mod node;
+mod novelty;
+pub mod pareto;
 mod population;
+mod render;
+mod rng;
 mod settings;
 mod species;
+mod substrate;
+pub mod tasks;
 
+pub use activations::{ActivationSet, Activations};
+pub use archipelago::{Archipelago, Topology};
+pub use coevolution::{Coevolution, OpponentSampling};
+pub use config::{ConfigError, ConfigFormat, SettingsError};
+pub use connection::Connection;
+pub use distance::{
+    CompatibilityDistance, GeneComparison, NeatDistance, NodeGeneDistance, ParameterDistance,
+};
+pub use environment::{
+    Aggregation, Controller, Environment, Episode, EpisodeRunner, Space, Step, DEFAULT_MAX_STEPS,
+};
+pub use error::HyperDarwinError;
+pub use evolvable::Evolvable;
+pub use experiment::{load_genome, save_genome, Experiment, ExperimentConfig, GenerationStats};
+pub use genome::Genome;
+pub use hall_of_fame::{HallOfFame, HallOfFameEntry};
+pub use history::{History, NodeMut};
 pub use hyper_tensor::HyperTensor;
+pub use interactive::InteractiveEvolution;
+pub use lineage::{Genealogy, GenealogyRecord, Mutation};
+pub use map_elites::{Dimension, MapElites};
+pub use node::Node;
+pub use novelty::NoveltyArchive;
 pub use population::Population;
+pub use render::{render_connectivity, Image, Renderer};
+pub use rng::seed;
+pub use settings::Encoding;
+pub use settings::FitnessMode;
 pub use settings::HyperSettings;
+pub use settings::InnovationTracking;
+pub use settings::ParetoScope;
+pub use settings::RepresentativeStrategy;
 pub use settings::Settings;
+pub use settings::Speciation;
+pub use species::{Species, SpeciesSnapshot};
+pub use substrate::{Network, Substrate};
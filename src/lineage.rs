@@ -0,0 +1,236 @@
+use crate::evolvable::Evolvable;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::io::{self, Write};
+use std::vec::Vec;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Mutation {
+    Weights(u32), // Number of connection weights that were mutated
+    AddConnection { innov: u32, from: u32, to: u32 },
+    AddNode { node: u32, split: u32 }, // `split` is the innovation of the split connection
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenealogyRecord {
+    pub id: u64,
+    pub parents: Vec<u64>,
+    pub birth_generation: u64,
+    pub last_generation: u64,
+    pub species_id: Option<usize>,
+    pub mutations: Vec<Mutation>,
+    #[serde(with = "crate::finite")]
+    pub fitness: f64,
+}
+
+// Every Genome that has been evaluated so far, keyed by id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Genealogy {
+    records: BTreeMap<u64, GenealogyRecord>,
+}
+
+impl Genealogy {
+    pub fn new() -> Self {
+        Self {
+            records: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    pub fn get(&self, id: u64) -> Option<&GenealogyRecord> {
+        self.records.get(&id)
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &GenealogyRecord> {
+        self.records.values()
+    }
+
+    // Elites carried over unmodified keep their id, so a Genome can be recorded
+    // more than once. Its best fitness and latest generation are kept.
+    pub fn record<G: Evolvable>(&mut self, genome: &G, generation: u64) {
+        let record = self
+            .records
+            .entry(genome.id())
+            .or_insert_with(|| GenealogyRecord {
+                id: genome.id(),
+                parents: genome.parents().to_vec(),
+                birth_generation: genome.birth_generation(),
+                last_generation: generation,
+                species_id: genome.species_id(),
+                mutations: genome.mutations().to_vec(),
+                fitness: genome.objective_fitness(),
+            });
+
+        record.last_generation = generation;
+        record.species_id = genome.species_id();
+
+        if genome.objective_fitness() > record.fitness {
+            record.fitness = genome.objective_fitness();
+        }
+    }
+
+    // Ids of every recorded ancestor of a Genome, nearest first
+    pub fn ancestors(&self, id: u64) -> Vec<u64> {
+        let mut res = Vec::<u64>::new();
+        let mut seen = HashSet::<u64>::new();
+        let mut frontier = VecDeque::from(vec![id]);
+
+        while let Some(curr) = frontier.pop_front() {
+            if let Some(record) = self.records.get(&curr) {
+                for &parent in &record.parents {
+                    if self.records.contains_key(&parent) && seen.insert(parent) {
+                        res.push(parent);
+                        frontier.push_back(parent);
+                    }
+                }
+            }
+        }
+
+        res
+    }
+
+    // The phylogeny as a tree in which every Genome hangs off its primary
+    // (fitter) parent. Branch lengths are in generations.
+    pub fn write_newick<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut children = BTreeMap::<u64, Vec<u64>>::new();
+        let mut roots = Vec::<u64>::new();
+
+        for record in self.records.values() {
+            match record.parents.first() {
+                Some(parent) if self.records.contains_key(parent) => {
+                    children.entry(*parent).or_default().push(record.id)
+                }
+                _ => roots.push(record.id),
+            }
+        }
+
+        let no_children = Vec::<u64>::new();
+        let mut res = String::from("(");
+
+        for (i, &root) in roots.iter().enumerate() {
+            if i > 0 {
+                res.push(',');
+            }
+
+            // Iterative post-order walk, long runs make for very deep trees
+            let mut stack = vec![(root, 0usize)];
+
+            while let Some((id, child_idx)) = stack.pop() {
+                let kids = children.get(&id).unwrap_or(&no_children);
+
+                if child_idx == 0 && !kids.is_empty() {
+                    res.push('(');
+                }
+
+                if child_idx < kids.len() {
+                    if child_idx > 0 {
+                        res.push(',');
+                    }
+
+                    stack.push((id, child_idx + 1));
+                    stack.push((kids[child_idx], 0));
+                    continue;
+                }
+
+                if !kids.is_empty() {
+                    res.push(')');
+                }
+
+                let record = &self.records[&id];
+                let branch = match record.parents.first().and_then(|p| self.records.get(p)) {
+                    Some(parent) => record
+                        .birth_generation
+                        .saturating_sub(parent.birth_generation),
+                    None => record.birth_generation,
+                };
+
+                res += &format!("g{}:{}", id, branch);
+            }
+        }
+
+        res += ");";
+
+        writeln!(writer, "{}", res)
+    }
+
+    // The full genealogy as a directed graph, with an edge from every parent
+    pub fn write_graphml<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="birth" for="node" attr.name="birth_generation" attr.type="long"/>"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="species" for="node" attr.name="species_id" attr.type="long"/>"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="fitness" for="node" attr.name="fitness" attr.type="double"/>"#
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="mutations" for="node" attr.name="mutations" attr.type="string"/>"#
+        )?;
+        writeln!(writer, r#"  <graph id="genealogy" edgedefault="directed">"#)?;
+
+        for record in self.records.values() {
+            writeln!(writer, r#"    <node id="g{}">"#, record.id)?;
+            writeln!(
+                writer,
+                r#"      <data key="birth">{}</data>"#,
+                record.birth_generation
+            )?;
+
+            if let Some(species) = record.species_id {
+                writeln!(writer, r#"      <data key="species">{}</data>"#, species)?;
+            }
+
+            writeln!(
+                writer,
+                r#"      <data key="fitness">{}</data>"#,
+                record.fitness
+            )?;
+            writeln!(
+                writer,
+                r#"      <data key="mutations">{:?}</data>"#,
+                record.mutations
+            )?;
+            writeln!(writer, "    </node>")?;
+        }
+
+        for record in self.records.values() {
+            for parent in record
+                .parents
+                .iter()
+                .filter(|p| self.records.contains_key(p))
+            {
+                writeln!(
+                    writer,
+                    r#"    <edge source="g{}" target="g{}"/>"#,
+                    parent, record.id
+                )?;
+            }
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")
+    }
+}
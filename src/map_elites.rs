@@ -0,0 +1,281 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::history::History;
+use crate::rng::thread_rng;
+use crate::settings::Settings;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::vec::Vec;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dimension {
+    pub min: f64,
+    pub max: f64,
+    pub resolution: usize,
+}
+
+impl Dimension {
+    pub fn new(min: f64, max: f64, resolution: usize) -> Result<Self, HyperDarwinError> {
+        let dim = Self {
+            min,
+            max,
+            resolution,
+        };
+
+        dim.validate()?;
+        Ok(dim)
+    }
+
+    // The fields are public, so dimensions built by hand or read from a file
+    // are checked again before use
+    pub fn validate(&self) -> Result<(), HyperDarwinError> {
+        if !self.min.is_finite() || !self.max.is_finite() || self.min >= self.max {
+            return Err(HyperDarwinError::InvalidDimension {
+                min: self.min,
+                max: self.max,
+            });
+        }
+
+        if self.resolution == 0 {
+            return Err(HyperDarwinError::ZeroResolution);
+        }
+
+        Ok(())
+    }
+
+    fn bin(&self, val: f64) -> usize {
+        let ratio = (val - self.min) / (self.max - self.min);
+        let bin = (ratio * self.resolution as f64).floor();
+
+        if bin < 0. {
+            0
+        } else if bin as usize >= self.resolution {
+            self.resolution - 1
+        } else {
+            bin as usize
+        }
+    }
+}
+
+// Grid archive keeping the best Genome found for every behavior cell
+#[derive(Serialize, Deserialize)]
+pub struct MapElites {
+    sets: Settings,
+    hist: History,
+    dims: Vec<Dimension>,
+    archive: Vec<Option<Genome>>,
+    pub evaluations: u64,
+}
+
+impl MapElites {
+    pub fn new(sets: Settings, dims: Vec<Dimension>) -> Result<Self, HyperDarwinError> {
+        let cells = Self::cells(&dims)?;
+        let hist = History::new(sets.genome_inputs(), sets.genome_outputs());
+
+        Ok(Self {
+            sets,
+            hist,
+            dims,
+            archive: (0..cells).map(|_| None).collect(),
+            evaluations: 0,
+        })
+    }
+
+    // Number of cells of a grid over the dimensions
+    fn cells(dims: &[Dimension]) -> Result<usize, HyperDarwinError> {
+        dims.iter().try_fold(1usize, |cells, dim| {
+            dim.validate()?;
+
+            cells
+                .checked_mul(dim.resolution)
+                .ok_or(HyperDarwinError::TooManyCells)
+        })
+    }
+
+    // Writes the whole archive, elites included, as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), HyperDarwinError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+
+        Ok(())
+    }
+
+    // Refuses archives whose dimensions are invalid or don't match their cells
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, HyperDarwinError> {
+        let reader = BufReader::new(File::open(path)?);
+        let map: Self = serde_json::from_reader(reader)?;
+
+        let cells = Self::cells(&map.dims)?;
+
+        if map.archive.len() != cells {
+            return Err(HyperDarwinError::Serialization(format!(
+                "the archive has {} cells, its dimensions make {}",
+                map.archive.len(),
+                cells
+            )));
+        }
+
+        Ok(map)
+    }
+
+    // Emits a batch of candidates. Until the archive holds anything the batch is
+    // made of fresh random Genomes, afterwards of mutated (and sometimes crossed
+    // over) copies of random elites.
+    pub fn ask(&mut self, batch: usize) -> Vec<Genome> {
+        let mut rng = thread_rng();
+
+        let elites = self.archive.iter().flatten().collect::<Vec<&Genome>>();
+        let mut candidates = Vec::<Genome>::with_capacity(batch);
+
+        for _ in 0..batch {
+            let mut child = if elites.is_empty() {
+                Genome::from_settings(&self.sets)
+            } else if rng.gen::<f64>() < self.sets.only_mut_rate {
+                elites.choose(&mut rng).unwrap().offspring()
+            } else {
+                let parent1 = elites.choose(&mut rng).unwrap();
+                let parent2 = elites.choose(&mut rng).unwrap();
+                Genome::crossover(parent1, parent2, &self.sets)
+            };
+
+            child.mutate(&mut self.hist, &self.sets);
+            child.clear_evaluation();
+            candidates.push(child);
+        }
+
+        candidates
+    }
+
+    // Inserts evaluated candidates, using their fitness and behavior descriptor.
+    // Returns how many of them made it into the archive.
+    pub fn tell(&mut self, genomes: Vec<Genome>) -> usize {
+        let mut inserted = 0;
+
+        for genome in genomes {
+            self.evaluations += 1;
+
+            let idx = match self.cell_index(&genome.behavior) {
+                Some(idx) => idx,
+                None => continue,
+            };
+
+            let replace = match &self.archive[idx] {
+                Some(elite) => genome.fitness > elite.fitness,
+                None => true,
+            };
+
+            if replace {
+                self.archive[idx] = Some(genome);
+                inserted += 1;
+            }
+        }
+
+        inserted
+    }
+
+    pub fn cell_index(&self, behavior: &[f64]) -> Option<usize> {
+        if behavior.len() != self.dims.len() {
+            return None;
+        }
+
+        let mut idx = 0;
+
+        for (dim, &val) in self.dims.iter().zip(behavior.iter()) {
+            if val.is_nan() {
+                return None;
+            }
+
+            idx = idx * dim.resolution + dim.bin(val);
+        }
+
+        Some(idx)
+    }
+
+    pub fn cell_coords(&self, mut idx: usize) -> Vec<usize> {
+        let mut coords = vec![0; self.dims.len()];
+
+        for (i, dim) in self.dims.iter().enumerate().rev() {
+            coords[i] = idx % dim.resolution;
+            idx /= dim.resolution;
+        }
+
+        coords
+    }
+
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.dims
+    }
+
+    pub fn get(&self, coords: &[usize]) -> Option<&Genome> {
+        if coords.len() != self.dims.len() {
+            return None;
+        }
+
+        let mut idx = 0;
+
+        for (dim, &c) in self.dims.iter().zip(coords.iter()) {
+            if c >= dim.resolution {
+                return None;
+            }
+
+            idx = idx * dim.resolution + c;
+        }
+
+        self.archive[idx].as_ref()
+    }
+
+    pub fn elites(&self) -> impl Iterator<Item = (Vec<usize>, &Genome)> {
+        self.archive
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, cell)| cell.as_ref().map(|g| (self.cell_coords(i), g)))
+    }
+
+    pub fn filled_cells(&self) -> usize {
+        self.archive.iter().flatten().count()
+    }
+
+    pub fn coverage(&self) -> f64 {
+        self.filled_cells() as f64 / self.archive.len() as f64
+    }
+
+    pub fn qd_score(&self) -> f64 {
+        self.archive.iter().flatten().map(|g| g.fitness).sum()
+    }
+
+    pub fn best(&self) -> Option<&Genome> {
+        self.archive
+            .iter()
+            .flatten()
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+    }
+
+    // Writes one CSV row per filled cell: cell coordinates, behavior and fitness
+    pub fn export_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut header = Vec::<String>::new();
+        header.extend((0..self.dims.len()).map(|i| format!("cell_{}", i)));
+        header.extend((0..self.dims.len()).map(|i| format!("behavior_{}", i)));
+        header.push(String::from("fitness"));
+
+        writeln!(writer, "{}", header.join(","))?;
+
+        for (coords, genome) in self.elites() {
+            let mut row = coords
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<String>>();
+            row.extend(genome.behavior.iter().map(|b| b.to_string()));
+            row.push(genome.fitness.to_string());
+
+            writeln!(writer, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+}
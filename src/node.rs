@@ -1,6 +1,8 @@
 use crate::activations::Activations;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+#[derive(Serialize, Deserialize)]
 pub struct Node {
     pub innov: u32,
     pub activation: Activations,
@@ -35,14 +37,7 @@ impl Node {
             return val;
         }
 
-        match self.activation {
-            Activations::Linear => val,
-            Activations::Absolute => val.abs(),
-            Activations::Sigmoid => 1. / (1. + (-4.9 * val).exp()),
-            Activations::Sine => val.sin(),
-            Activations::Cosine => val.cos(),
-            Activations::Gaussian => (-(val.powi(2) / 2.)).exp(),
-        }
+        self.activation.apply(val)
     }
 }
 
@@ -0,0 +1,167 @@
+use crate::evolvable::Evolvable;
+use crate::settings::Settings;
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::vec::Vec;
+
+#[derive(Serialize, Deserialize)]
+pub struct NoveltyArchive {
+    behaviors: VecDeque<Vec<f64>>,
+    pub threshold: f64,
+    stale_generations: u32,
+}
+
+impl NoveltyArchive {
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            behaviors: VecDeque::new(),
+            threshold,
+            stale_generations: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    pub fn behaviors(&self) -> impl Iterator<Item = &Vec<f64>> {
+        self.behaviors.iter()
+    }
+
+    // Length every behavior must have: that of the archived ones, or else the
+    // most common one in the population
+    pub fn descriptor_len<G: Evolvable>(&self, population: &[G]) -> Option<usize> {
+        if let Some(b) = self.behaviors.front() {
+            return Some(b.len());
+        }
+
+        let mut counts = HashMap::new();
+
+        for genome in population.iter().filter(|g| !g.behavior().is_empty()) {
+            *counts.entry(genome.behavior().len()).or_insert(0) += 1;
+        }
+
+        counts
+            .into_iter()
+            .max_by_key(|&(len, count)| (count, Reverse(len)))
+            .map(|(len, _)| len)
+    }
+
+    // Sparseness of a behavior: mean distance to its k nearest neighbours among
+    // the current population and the archive. Behaviors of another length than
+    // the archive's are neither scored nor used as neighbours.
+    pub fn sparseness<G: Evolvable>(&self, idx: usize, population: &[G], k: usize) -> f64 {
+        let len = match self.descriptor_len(population) {
+            Some(len) => len,
+            None => return 0.,
+        };
+
+        self.sparseness_of(idx, population, k, len)
+    }
+
+    fn sparseness_of<G: Evolvable>(
+        &self,
+        idx: usize,
+        population: &[G],
+        k: usize,
+        len: usize,
+    ) -> f64 {
+        let behavior = population[idx].behavior();
+
+        if behavior.len() != len || len == 0 || k == 0 {
+            return 0.;
+        }
+
+        let mut distances = population
+            .iter()
+            .enumerate()
+            .filter(|&(i, g)| i != idx && g.behavior().len() == len)
+            .map(|(_, g)| behavior_distance(behavior, g.behavior()))
+            .chain(
+                self.behaviors
+                    .iter()
+                    .map(|b| behavior_distance(behavior, b)),
+            )
+            .collect::<Vec<f64>>();
+
+        if distances.is_empty() {
+            return 0.;
+        }
+
+        distances.sort_unstable_by(|a, b| a.total_cmp(b));
+
+        let neighbours = k.min(distances.len());
+
+        distances.iter().take(neighbours).sum::<f64>() / neighbours as f64
+    }
+
+    // Scores every genome's novelty, then archives the behaviors that cleared
+    // the threshold. The threshold rises when too many get in and decays when
+    // nothing has been added for a while. Genomes whose behavior has the wrong
+    // length get no novelty and are never archived.
+    pub fn evaluate<G: Evolvable>(&mut self, population: &mut [G], sets: &Settings) {
+        let len = self.descriptor_len(population).unwrap_or(0);
+
+        let scores = (0..population.len())
+            .map(|i| self.sparseness_of(i, population, sets.novelty_k, len))
+            .collect::<Vec<f64>>();
+
+        let mut added = 0;
+
+        for (genome, score) in population.iter_mut().zip(scores) {
+            genome.set_novelty(score);
+
+            if score > self.threshold && len > 0 && genome.behavior().len() == len {
+                self.behaviors.push_back(genome.behavior().to_vec());
+                added += 1;
+            }
+        }
+
+        while sets.novelty_archive_size > 0 && self.behaviors.len() > sets.novelty_archive_size {
+            self.behaviors.pop_front();
+        }
+
+        if added == 0 {
+            self.stale_generations += 1;
+        } else {
+            self.stale_generations = 0;
+        }
+
+        if added > sets.novelty_max_additions {
+            self.threshold *= sets.novelty_threshold_raise;
+        } else if self.stale_generations >= sets.novelty_stale_generations {
+            self.threshold *= sets.novelty_threshold_decay;
+            self.stale_generations = 0;
+        }
+    }
+
+    pub fn clear(&mut self, threshold: f64) {
+        self.behaviors.clear();
+        self.threshold = threshold;
+        self.stale_generations = 0;
+    }
+}
+
+// Behaviors of different lengths can't be compared, the archive only ever
+// passes it behaviors of the same length
+pub fn behavior_distance(a: &[f64], b: &[f64]) -> f64 {
+    debug_assert_eq!(
+        a.len(),
+        b.len(),
+        "comparing a behavior of {} values with one of {}",
+        a.len(),
+        b.len()
+    );
+
+    a.iter()
+        .zip(b.iter())
+        .fold(0., |acc, (x, y)| acc + (x - y).powi(2))
+        .sqrt()
+}
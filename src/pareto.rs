@@ -0,0 +1,186 @@
+use crate::error::HyperDarwinError;
+use crate::evolvable::Evolvable;
+
+use std::cmp::Ordering;
+use std::f64;
+use std::vec::Vec;
+
+// All objectives are maximized. Both sides must score the same objectives,
+// `rank_population` checks that before comparing any.
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut strictly_better = false;
+
+    for (x, y) in a.iter().zip(b.iter()) {
+        if x < y {
+            return false;
+        }
+
+        if x > y {
+            strictly_better = true;
+        }
+    }
+
+    strictly_better
+}
+
+// Fast non-dominated sort. Returns the fronts as lists of indices, best front first.
+pub fn non_dominated_sort(objectives: &[&[f64]]) -> Vec<Vec<usize>> {
+    let len = objectives.len();
+
+    let mut dominated_by = vec![Vec::<usize>::new(); len];
+    let mut domination_count = vec![0usize; len];
+    let mut fronts = vec![Vec::<usize>::new()];
+
+    for p in 0..len {
+        for q in 0..len {
+            if p == q {
+                continue;
+            }
+
+            if dominates(objectives[p], objectives[q]) {
+                dominated_by[p].push(q);
+            } else if dominates(objectives[q], objectives[p]) {
+                domination_count[p] += 1;
+            }
+        }
+
+        if domination_count[p] == 0 {
+            fronts[0].push(p);
+        }
+    }
+
+    let mut current = 0;
+
+    while !fronts[current].is_empty() {
+        let mut next_front = Vec::<usize>::new();
+
+        for &p in &fronts[current] {
+            for &q in &dominated_by[p] {
+                domination_count[q] -= 1;
+
+                if domination_count[q] == 0 {
+                    next_front.push(q);
+                }
+            }
+        }
+
+        fronts.push(next_front);
+        current += 1;
+    }
+
+    fronts.pop();
+    fronts
+}
+
+// Crowding distance of every member of a front, in the same order as the front
+pub fn crowding_distance(objectives: &[&[f64]], front: &[usize]) -> Vec<f64> {
+    if front.len() < 3 {
+        return vec![f64::INFINITY; front.len()];
+    }
+
+    let mut distances = vec![0.; front.len()];
+
+    let num_objectives = front
+        .iter()
+        .map(|&i| objectives[i].len())
+        .min()
+        .unwrap_or(0);
+
+    (0..num_objectives).for_each(|m| {
+        let value = |i: usize| objectives[front[i]][m];
+
+        let mut order = (0..front.len()).collect::<Vec<usize>>();
+        order.sort_unstable_by(|&a, &b| value(a).total_cmp(&value(b)));
+
+        let first = order[0];
+        let last = order[order.len() - 1];
+        let range = value(last) - value(first);
+
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+
+        if range <= 0. {
+            return;
+        }
+
+        for w in order.windows(3) {
+            distances[w[1]] += (value(w[2]) - value(w[0])) / range;
+        }
+    });
+
+    distances
+}
+
+// Crowded comparison: lower rank wins, ties are broken by larger crowding distance
+pub fn crowded_cmp<G: Evolvable>(a: &G, b: &G) -> Ordering {
+    a.pareto_rank()
+        .cmp(&b.pareto_rank())
+        .then_with(|| b.crowding_distance().total_cmp(&a.crowding_distance()))
+}
+
+// Ranks the population and turns (rank, crowding) into a scalar fitness that
+// orders genomes exactly like the crowded comparison does, so that fitness
+// sharing and offspring allocation keep working unchanged. The fitness is
+// scaled by the number of fronts, so that groups ranked on their own (see
+// `ParetoScope::Species`) compete on the same footing.
+//
+// By default fronts span the whole population, as in NSGA-II, not each
+// species: a species whose best member is dominated elsewhere gets fewer
+// offspring, and speciation only keeps the trade-offs from being crowded out.
+// The task's own fitness stays in `objective_fitness`.
+//
+// Fails, before ranking anyone, unless every genome scores as many objectives.
+pub(crate) fn rank_population<G: Evolvable>(population: &mut [G]) -> Result<(), HyperDarwinError> {
+    if let Some(first) = population.first() {
+        let expected = first.objectives().len();
+
+        if let Some((genome, g)) = population
+            .iter()
+            .enumerate()
+            .find(|(_, g)| g.objectives().len() != expected)
+        {
+            return Err(HyperDarwinError::ObjectiveCountMismatch {
+                genome,
+                expected,
+                actual: g.objectives().len(),
+            });
+        }
+    }
+
+    let fronts = {
+        let objectives = population
+            .iter()
+            .map(|g| g.objectives())
+            .collect::<Vec<&[f64]>>();
+
+        non_dominated_sort(&objectives)
+            .into_iter()
+            .map(|front| {
+                let crowding = crowding_distance(&objectives, &front);
+                front
+                    .into_iter()
+                    .zip(crowding)
+                    .collect::<Vec<(usize, f64)>>()
+            })
+            .collect::<Vec<Vec<(usize, f64)>>>()
+    };
+
+    let num_fronts = fronts.len();
+
+    for (rank, front) in fronts.into_iter().enumerate() {
+        for (idx, crowding) in front {
+            let genome = &mut population[idx];
+            genome.set_pareto_rank(rank, crowding);
+
+            let crowding_bonus = if crowding.is_infinite() {
+                0.999
+            } else {
+                0.999 * crowding / (1. + crowding)
+            };
+
+            genome.set_fitness(((num_fronts - rank) as f64 + crowding_bonus) / num_fronts as f64);
+        }
+    }
+
+    Ok(())
+}
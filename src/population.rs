@@ -1,30 +1,76 @@
+use crate::clustering::{self, Clustering};
+use crate::error::HyperDarwinError;
+use crate::evolvable::Evolvable;
 use crate::genome::Genome;
+use crate::hall_of_fame::{HallOfFame, HallOfFameEntry};
 use crate::history::History;
-use crate::settings::Settings;
+use crate::lineage::Genealogy;
+use crate::novelty::NoveltyArchive;
+use crate::pareto;
+use crate::settings::{FitnessMode, InnovationTracking, ParetoScope, Settings, Speciation};
 use crate::species::Species;
 
+use serde::{Deserialize, Serialize};
+
 use std::vec::Vec;
 
-pub struct Population {
+#[derive(Serialize, Deserialize)]
+pub struct Population<G = Genome> {
     sets: Settings,
-    population: Vec<Genome>,
-    species: Vec<Species>,
-    hist: History,
+    population: Vec<G>,
+    species: Vec<Species<G>>,
+    extinct_species: Vec<Species<G>>,
+    pub(crate) hist: History,
+    archive: NoveltyArchive,
+    pareto_front: Vec<G>,
+    hall_of_fame: HallOfFame<G>,
+    generation_champions: Vec<HallOfFameEntry<G>>,
+    species_champions: Vec<HallOfFameEntry<G>>,
+    next_species_id: usize,
+    genealogy: Genealogy,
+    #[serde(with = "crate::finite")]
     pub best_fitness: f64,
-    pub best_genome: Option<Genome>,
+    pub best_genome: Option<G>,
     pub generations: u64,
 }
 
 impl Population {
     pub fn new(sets: Settings) -> Self {
-        let inputs = if sets.third_param { 6 } else { 4 };
+        Self::with_encoding(sets)
+    }
+
+    // Like `new`, but refuses invalid Settings
+    pub fn try_new(sets: Settings) -> Result<Self, HyperDarwinError> {
+        sets.validate()?;
+
+        Ok(Self::new(sets))
+    }
+}
+
+impl<G: Evolvable> Population<G> {
+    // A Population of a custom encoding, e.g. `Population::<MyGenome>::with_encoding(sets)`.
+    // Panics on a pop_size of 0, which `try_new` reports with the other settings.
+    pub fn with_encoding(sets: Settings) -> Self {
+        assert!(sets.pop_size > 0, "a Population needs a pop_size above 0");
+
+        let hist = History::new(sets.genome_inputs(), sets.genome_outputs());
 
         let pop_size = sets.pop_size as usize;
+        let threshold = sets.novelty_threshold;
+        let hof_size = sets.hall_of_fame_size;
         let mut pop = Self {
-            sets: sets,
-            population: Vec::<Genome>::with_capacity(pop_size),
+            sets,
+            population: Vec::<G>::with_capacity(pop_size),
             species: Vec::new(),
-            hist: History::new(inputs, 1),
+            extinct_species: Vec::new(),
+            hist,
+            archive: NoveltyArchive::new(threshold),
+            pareto_front: Vec::new(),
+            hall_of_fame: HallOfFame::new(hof_size),
+            generation_champions: Vec::new(),
+            species_champions: Vec::new(),
+            next_species_id: 0,
+            genealogy: Genealogy::new(),
             best_fitness: 0.,
             best_genome: None,
             generations: 0,
@@ -38,34 +84,113 @@ impl Population {
     fn reset(&mut self) {
         self.population.clear();
         self.species.clear();
+        self.extinct_species.clear();
         self.best_fitness = 0.;
         self.best_genome = None;
         self.generations = 0;
-        let inputs = if self.sets.third_param { 4 } else { 6 };
-        self.hist = History::new(inputs, 1);
+        self.archive.clear(self.sets.novelty_threshold);
+        self.pareto_front.clear();
+        self.hall_of_fame = HallOfFame::new(self.sets.hall_of_fame_size);
+        self.generation_champions.clear();
+        self.species_champions.clear();
+        self.next_species_id = 0;
+        self.genealogy.clear();
+        self.hist = History::new(self.sets.genome_inputs(), self.sets.genome_outputs());
 
         for _ in 0..self.sets.pop_size {
-            let genome = Genome::new(inputs, 1, false);
+            let genome = G::initial(&self.sets);
             self.population.push(genome);
         }
     }
 
+    // Panics where `try_next_generation` would fail
     pub fn next_generation(&mut self) {
+        if let Err(err) = self.try_next_generation() {
+            panic!("{}", err);
+        }
+    }
+
+    // Breeds the next generation. Fails when multi-objective Genomes don't all
+    // score as many objectives, leaving the evaluation in place to fix and retry,
+    // or when the citizens were all taken out.
+    pub fn try_next_generation(&mut self) -> Result<(), HyperDarwinError> {
+        if self.population.is_empty() {
+            return Err(HyperDarwinError::EmptyPopulation);
+        }
+
+        // A NaN would poison every species' share of the offspring
+        for genome in self.population.iter_mut().filter(|g| g.fitness().is_nan()) {
+            genome.set_fitness(0.);
+        }
+
         self.population
-            .sort_unstable_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+            .sort_unstable_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        if self.population[0].fitness() > self.best_fitness {
+            self.best_fitness = self.population[0].fitness();
+            self.best_genome = Some(self.population[0].clone());
+        }
+
+        self.population
+            .iter_mut()
+            .for_each(|g| g.set_objective_fitness(g.fitness()));
+
+        match self.sets.fitness_mode {
+            FitnessMode::Objective => {}
+            FitnessMode::Novelty | FitnessMode::Hybrid(_) => self.apply_novelty(),
+            FitnessMode::MultiObjective => self.apply_pareto_ranking()?,
+        }
 
         let mut this_champ = self.population[0].clone();
-        if this_champ.fitness > self.best_fitness {
-            self.best_fitness = this_champ.fitness;
-            self.best_genome = Some(this_champ.clone());
+        this_champ.clear_evaluation();
+
+        let pop_size = self.sets.pop_size;
+        let global_elites = self
+            .sets
+            .global_elitism
+            .min((pop_size as usize).saturating_sub(1));
+
+        let mut progeny = Vec::<G>::with_capacity(pop_size as usize);
+
+        for elite in self.population.iter().take(global_elites) {
+            let mut elite = elite.clone();
+            elite.clear_evaluation();
+            progeny.push(elite);
         }
 
-        this_champ.fitness = 0.;
+        let global_elites = progeny.len();
 
         self.speciate_population();
 
+        // The population-wide ranking above still picks the global elites and
+        // the Pareto front, species are then ranked again on their own
+        if self.sets.fitness_mode == FitnessMode::MultiObjective
+            && self.sets.pareto_scope == ParetoScope::Species
+        {
+            for species in self.species.iter_mut() {
+                pareto::rank_population(&mut species.genomes)?;
+            }
+        }
+
+        self.species.iter_mut().for_each(|s| s.sort_genomes());
+
+        self.record_champions();
+
+        let generation = self.generations;
+        let strategy = self.sets.representative;
+
+        self.species.iter_mut().for_each(|s| {
+            s.record_history(generation);
+            s.update_representative(strategy);
+        });
+
+        if self.sets.track_lineage {
+            for genome in self.species.iter().flat_map(|s| s.genomes.iter()) {
+                self.genealogy.record(genome, self.generations);
+            }
+        }
+
         self.species.iter_mut().for_each(|s| {
-            s.sort_genomes();
             s.update_stagnancy();
             s.fitness_sharing();
             s.cull_lower_half();
@@ -73,68 +198,417 @@ impl Population {
 
         let allowed_stagnancy = self.sets.allowed_stagnancy;
 
-        self.species.retain(|s| s.stagnancy < allowed_stagnancy);
+        self.retire_species(|s| s.stagnancy >= allowed_stagnancy);
 
         let total_avg_fitness = self.species.iter().fold(0., |acc, s| acc + s.avg_fitness);
 
-        let pop_size = self.sets.pop_size;
+        if self.sets.innovation_tracking == InnovationTracking::Generation {
+            self.hist.reset_tracking();
+        }
+
+        let offspring_pool = (pop_size as usize - global_elites) as f64;
 
         self.species.iter_mut().for_each(|s| {
-            s.assigned_offspring = (s.avg_fitness / total_avg_fitness * pop_size as f64) as usize;
+            s.assigned_offspring = (s.avg_fitness / total_avg_fitness * offspring_pool) as usize;
         });
 
-        self.species.retain(|s| s.assigned_offspring > 0);
+        // Rounding down can starve every species when the pool is small, the
+        // fittest one is kept rather than losing them all
+        if self.species.iter().all(|s| s.assigned_offspring == 0) {
+            if let Some(best) = self
+                .species
+                .iter_mut()
+                .max_by(|a, b| a.avg_fitness.total_cmp(&b.avg_fitness))
+            {
+                best.assigned_offspring = offspring_pool as usize;
+            }
+        }
 
-        let mut progeny = Vec::<Genome>::with_capacity(pop_size as usize);
+        self.retire_species(|s| s.assigned_offspring == 0);
 
-        for species in &self.species {
+        for (i, species) in self.species.iter().enumerate() {
             let mut new_offspring = species.assigned_offspring;
 
-            if species.genomes.len() > 3 {
-                let mut champ = species.genomes[0].clone();
-                champ.fitness = 0.;
-                progeny.push(champ);
-                new_offspring -= 1;
+            if species.genomes.len() >= self.sets.elitism_min_species_size {
+                // Global elites already carried over aren't copied a second time
+                let elites = species
+                    .genomes
+                    .iter()
+                    .filter(|g| {
+                        !progeny[..global_elites]
+                            .iter()
+                            .any(|e| e.id() == g.id() && e.same_genes(g))
+                    })
+                    .take(self.sets.elitism.min(new_offspring))
+                    .cloned()
+                    .collect::<Vec<G>>();
+
+                new_offspring -= elites.len();
+
+                for mut elite in elites {
+                    elite.clear_evaluation();
+                    progeny.push(elite);
+                }
             }
 
-            for mut child in species.produce_offspring(new_offspring, &self.sets) {
+            let others = self
+                .species
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, s)| s)
+                .collect::<Vec<&Species<G>>>();
+
+            for mut child in species.produce_offspring(new_offspring, &others, &self.sets) {
                 child.mutate(&mut self.hist, &self.sets);
-                child.fitness = 0.;
+                child.clear_evaluation();
+                child.set_birth_generation(self.generations + 1);
                 progeny.push(child);
             }
         }
 
         if progeny.len() < pop_size as usize {
             while progeny.len() < pop_size as usize {
-                let mut another_child = this_champ.clone();
+                let mut another_child = this_champ.offspring();
                 another_child.mutate(&mut self.hist, &self.sets);
+                another_child.set_birth_generation(self.generations + 1);
                 progeny.push(another_child);
             }
         }
 
         self.population = progeny;
         self.generations += 1;
+
+        Ok(())
     }
 
-    pub fn get_citizens(&mut self) -> &mut Vec<Genome> {
+    pub fn get_citizens(&mut self) -> &mut Vec<G> {
         &mut self.population
     }
 
+    pub fn citizens(&self) -> &[G] {
+        &self.population
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.sets
+    }
+
+    pub(crate) fn top_genomes(&self, amt: usize) -> Vec<G> {
+        let mut ranked = self.population.iter().collect::<Vec<&G>>();
+        ranked.sort_unstable_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        ranked.into_iter().take(amt).cloned().collect()
+    }
+
+    // Replaces the worst Genomes of the population with the fittest migrants.
+    // However many islands send them, migrants never take more than half of
+    // the population, the locals keep the rest.
+    pub(crate) fn immigrate(&mut self, mut migrants: Vec<G>) {
+        migrants.sort_unstable_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        migrants.truncate(self.population.len() / 2);
+
+        if migrants.is_empty() {
+            return;
+        }
+
+        self.population
+            .sort_unstable_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        let keep = self.population.len() - migrants.len();
+        self.population.truncate(keep);
+        self.population.extend(migrants);
+    }
+
+    pub fn evaluate_behaviors<F>(&mut self, mut behavior_fn: F)
+    where
+        F: FnMut(&G) -> Vec<f64>,
+    {
+        for genome in self.population.iter_mut() {
+            let behavior = behavior_fn(genome);
+            genome.set_behavior(behavior);
+        }
+    }
+
+    pub fn novelty_archive(&self) -> &NoveltyArchive {
+        &self.archive
+    }
+
+    pub fn species(&self) -> &[Species<G>] {
+        &self.species
+    }
+
+    pub fn extinct_species(&self) -> &[Species<G>] {
+        &self.extinct_species
+    }
+
+    pub fn hall_of_fame(&self) -> &HallOfFame<G> {
+        &self.hall_of_fame
+    }
+
+    pub fn generation_champions(&self) -> &[HallOfFameEntry<G>] {
+        &self.generation_champions
+    }
+
+    pub fn species_champions(&self) -> &[HallOfFameEntry<G>] {
+        &self.species_champions
+    }
+
+    // Champions are ranked by objective fitness, whatever the fitness mode
+    // made of `fitness`
+    fn record_champions(&mut self) {
+        let generation = self.generations;
+        let mut gen_champ: Option<HallOfFameEntry<G>> = None;
+
+        for species in &self.species {
+            let champ = match species
+                .genomes
+                .iter()
+                .max_by(|a, b| a.objective_fitness().total_cmp(&b.objective_fitness()))
+            {
+                Some(champ) => champ,
+                None => continue,
+            };
+
+            let entry = HallOfFameEntry::new(champ, generation, species.id);
+
+            match self
+                .species_champions
+                .iter_mut()
+                .find(|e| e.species_id == species.id)
+            {
+                Some(best) if best.fitness >= entry.fitness => {}
+                Some(best) => *best = entry.clone(),
+                None => self.species_champions.push(entry.clone()),
+            }
+
+            if gen_champ.as_ref().is_none_or(|c| entry.fitness > c.fitness) {
+                gen_champ = Some(entry.clone());
+            }
+
+            for genome in &species.genomes {
+                self.hall_of_fame
+                    .consider(HallOfFameEntry::new(genome, generation, species.id));
+            }
+        }
+
+        if let Some(champ) = gen_champ {
+            self.generation_champions.push(champ);
+        }
+    }
+
+    pub fn genealogy(&self) -> &Genealogy {
+        &self.genealogy
+    }
+
+    pub fn pareto_front(&self) -> &[G] {
+        &self.pareto_front
+    }
+
+    fn apply_pareto_ranking(&mut self) -> Result<(), HyperDarwinError> {
+        pareto::rank_population(&mut self.population)?;
+
+        self.population.sort_unstable_by(pareto::crowded_cmp);
+
+        self.pareto_front = self
+            .population
+            .iter()
+            .filter(|g| g.pareto_rank() == 0)
+            .cloned()
+            .collect();
+
+        Ok(())
+    }
+
+    fn apply_novelty(&mut self) {
+        self.archive.evaluate(&mut self.population, &self.sets);
+
+        let novelty_weight = match self.sets.fitness_mode {
+            FitnessMode::Novelty => 1.,
+            FitnessMode::Hybrid(w) => w,
+            _ => 0.,
+        };
+
+        // Novelty is a distance in behavior space and fitness whatever the task
+        // makes it, both are scaled to [0, 1] by the population's best before
+        // being mixed
+        let max_fitness = self
+            .population
+            .iter()
+            .map(|g| g.fitness())
+            .fold(0., f64::max);
+        let max_novelty = self
+            .population
+            .iter()
+            .map(|g| g.novelty())
+            .fold(0., f64::max);
+
+        let scaled = |val: f64, max: f64| if max > 0. { val / max } else { 0. };
+
+        for genome in self.population.iter_mut() {
+            let fitness = (1. - novelty_weight) * scaled(genome.fitness(), max_fitness)
+                + novelty_weight * scaled(genome.novelty(), max_novelty);
+            genome.set_fitness(fitness);
+        }
+
+        self.population
+            .sort_unstable_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+    }
+
+    // Moves the species matching the predicate over to the extinct species
+    fn retire_species<F>(&mut self, is_extinct: F)
+    where
+        F: Fn(&Species<G>) -> bool,
+    {
+        let (extinct, alive): (Vec<Species<G>>, Vec<Species<G>>) =
+            self.species.drain(..).partition(|s| is_extinct(s));
+
+        self.species = alive;
+
+        for mut species in extinct {
+            species.genomes.clear();
+            self.extinct_species.push(species);
+        }
+    }
+
     fn speciate_population(&mut self) {
         for species in &mut self.species {
             species.genomes.clear();
         }
 
-        'outer: for genome in self.population.drain(..) {
+        match self.sets.speciation {
+            Speciation::Threshold => self.speciate_by_threshold(),
+            Speciation::KMedoids(k) => self.speciate_by_clustering(k, k),
+            Speciation::AdaptiveKMedoids { min, max } => self.speciate_by_clustering(min, max),
+        }
+    }
+
+    fn speciate_by_threshold(&mut self) {
+        'outer: for mut genome in self.population.drain(..) {
             for species in &mut self.species {
                 if species.can_accomodate(&genome, &self.sets) {
+                    genome.set_species_id(Some(species.id));
                     species.add_genome(genome);
                     continue 'outer;
                 }
             }
 
-            let new_spec = Species::new(genome);
+            genome.set_species_id(Some(self.next_species_id));
+            let new_spec = Species::new(self.next_species_id, genome, self.generations);
+            self.next_species_id += 1;
             self.species.push(new_spec);
         }
     }
+
+    // Partitions the population with k-medoids. Each medoid is matched to the
+    // closest representative of the previous generation so that species ids
+    // carry over, unmatched clusters found new species.
+    fn speciate_by_clustering(&mut self, min_k: usize, max_k: usize) {
+        let genomes = self.population.drain(..).collect::<Vec<G>>();
+
+        if genomes.is_empty() {
+            return;
+        }
+
+        let capped = |d: f64| if d.is_finite() { d } else { 1e9 };
+
+        let dist = genomes
+            .iter()
+            .map(|a| {
+                genomes
+                    .iter()
+                    .map(|b| capped(a.distance(b, &self.sets)))
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<Vec<f64>>>();
+
+        let seeds = self
+            .species
+            .iter()
+            .filter_map(|s| {
+                (0..genomes.len()).min_by(|&a, &b| {
+                    let da = capped(s.representative().distance(&genomes[a], &self.sets));
+                    let db = capped(s.representative().distance(&genomes[b], &self.sets));
+                    da.total_cmp(&db)
+                })
+            })
+            .collect::<Vec<usize>>();
+
+        let min_k = min_k.clamp(1, genomes.len());
+        let max_k = max_k.clamp(min_k, genomes.len());
+
+        let clustering = (min_k..=max_k)
+            .map(|k| {
+                let clustering = clustering::k_medoids(&dist, k, &seeds);
+                let score = clustering::silhouette(&dist, &clustering);
+                (clustering, score)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(clustering, _)| clustering)
+            .unwrap();
+
+        let matches = self.match_medoids(&clustering, &genomes);
+
+        let mut genomes = genomes.into_iter().map(Some).collect::<Vec<Option<G>>>();
+
+        for (cluster, &medoid) in clustering.medoids.iter().enumerate() {
+            let members = clustering.members(cluster).collect::<Vec<usize>>();
+
+            let species_idx = match matches[cluster] {
+                Some(idx) => idx,
+                None => {
+                    let mut head = genomes[medoid].take().unwrap();
+                    head.set_species_id(Some(self.next_species_id));
+                    self.species
+                        .push(Species::new(self.next_species_id, head, self.generations));
+                    self.next_species_id += 1;
+                    self.species.len() - 1
+                }
+            };
+
+            let species = &mut self.species[species_idx];
+
+            if let Some(repr) = genomes[medoid].as_ref() {
+                species.set_representative(repr.clone());
+            }
+
+            for member in members {
+                if let Some(mut genome) = genomes[member].take() {
+                    genome.set_species_id(Some(species.id));
+                    species.add_genome(genome);
+                }
+            }
+        }
+    }
+
+    // Greedy one-to-one matching of medoids to existing species, closest pairs first
+    fn match_medoids(&self, clustering: &Clustering, genomes: &[G]) -> Vec<Option<usize>> {
+        let mut pairs = Vec::<(usize, usize, f64)>::new();
+
+        for (cluster, &medoid) in clustering.medoids.iter().enumerate() {
+            for (idx, species) in self.species.iter().enumerate() {
+                let d = species
+                    .representative()
+                    .distance(&genomes[medoid], &self.sets);
+
+                if d.is_finite() {
+                    pairs.push((cluster, idx, d));
+                }
+            }
+        }
+
+        pairs.sort_unstable_by(|a, b| a.2.total_cmp(&b.2));
+
+        let mut matches = vec![None; clustering.medoids.len()];
+        let mut taken = vec![false; self.species.len()];
+
+        for (cluster, idx, _) in pairs {
+            if matches[cluster].is_none() && !taken[idx] {
+                matches[cluster] = Some(idx);
+                taken[idx] = true;
+            }
+        }
+
+        matches
+    }
 }
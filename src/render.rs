@@ -0,0 +1,278 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::substrate::Substrate;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::vec::Vec;
+
+// Co-ordinates of `len` pixel centers spanning [-1, 1], or the center alone
+fn axis(len: usize) -> Vec<f64> {
+    match len {
+        1 => vec![0.],
+        _ => (0..len)
+            .map(|i| -1. + 2. * i as f64 / (len - 1) as f64)
+            .collect(),
+    }
+}
+
+// Maps [-1, 1] onto 0..=255
+fn to_byte(val: f64) -> u8 {
+    ((val.clamp(-1., 1.) + 1.) / 2. * 255.).round() as u8
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    b << 16 | a
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut body = kind.to_vec();
+    body.extend_from_slice(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(&body)?;
+    writer.write_all(&crc32(&body).to_be_bytes())
+}
+
+// An 8-bit grayscale or RGB image, row by row from the top
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize, // 1 for grayscale, 3 for RGB
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, channels: usize) -> Self {
+        Self {
+            width,
+            height,
+            channels,
+            pixels: vec![0; width * height * channels],
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> &[u8] {
+        let start = (y * self.width + x) * self.channels;
+        &self.pixels[start..start + self.channels]
+    }
+
+    // Every pixel blown up into a `factor`×`factor` block
+    pub fn scaled(&self, factor: usize) -> Self {
+        let mut image = Self::new(self.width * factor, self.height * factor, self.channels);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let start = (y * image.width + x) * self.channels;
+
+                image.pixels[start..start + self.channels]
+                    .copy_from_slice(self.pixel(x / factor, y / factor));
+            }
+        }
+
+        image
+    }
+
+    // Binary PGM for grayscale or PPM for RGB
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let magic = if self.channels == 1 { "P5" } else { "P6" };
+
+        write!(writer, "{}\n{} {}\n255\n", magic, self.width, self.height)?;
+        writer.write_all(&self.pixels)
+    }
+
+    // An uncompressed PNG, stored deflate blocks keep the encoder tiny
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let color_type = if self.channels == 1 { 0 } else { 2 };
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+        // Every scanline starts with filter type 0
+        let mut raw = Vec::with_capacity((self.width * self.channels + 1) * self.height);
+
+        for row in self.pixels.chunks(self.width.max(1) * self.channels) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        let mut data = vec![0x78, 0x01];
+        let blocks = raw.chunks(65535).collect::<Vec<_>>();
+
+        for (i, block) in blocks.iter().enumerate() {
+            let len = block.len() as u16;
+
+            data.push((i + 1 == blocks.len()) as u8);
+            data.extend_from_slice(&len.to_le_bytes());
+            data.extend_from_slice(&(!len).to_le_bytes());
+            data.extend_from_slice(block);
+        }
+
+        if blocks.is_empty() {
+            data.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+        }
+
+        data.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+        writer.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+        write_chunk(&mut writer, b"IHDR", &header)?;
+        write_chunk(&mut writer, b"IDAT", &data)?;
+        write_chunk(&mut writer, b"IEND", &[])
+    }
+
+    // Writes a PNG when the path ends in .png and a PGM or PPM otherwise
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), HyperDarwinError> {
+        let path = path.as_ref();
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") => self.write_png(&mut writer)?,
+            _ => self.write_ppm(&mut writer)?,
+        }
+
+        Ok(writer.flush()?)
+    }
+}
+
+// Draws the pattern of a CPPN by querying it at every pixel of a
+// `width`×`height` grid over [-1, 1]², x growing rightwards and y downwards.
+// The CPPN takes x and y, followed by the distance from the center and a
+// constant 1 bias when those are enabled. A CPPN with 3 or more outputs draws
+// its first three as red, green and blue, any other one draws its first
+// output in grayscale, -1 being black and 1 white.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Renderer {
+    pub width: usize,
+    pub height: usize,
+    pub distance: bool,
+    pub bias: bool,
+}
+
+impl Renderer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            distance: false,
+            bias: false,
+        }
+    }
+
+    pub fn distance(mut self, distance: bool) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn bias(mut self, bias: bool) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    // Number of inputs the CPPN must have
+    pub fn inputs(&self) -> usize {
+        2 + self.distance as usize + self.bias as usize
+    }
+
+    pub fn render(&self, genome: &Genome) -> Result<Image, HyperDarwinError> {
+        if genome.inputs() as usize != self.inputs() {
+            return Err(HyperDarwinError::InputSizeMismatch {
+                expected: self.inputs(),
+                actual: genome.inputs() as usize,
+            });
+        }
+
+        let channels = if genome.outputs() >= 3 { 3 } else { 1 };
+        let mut image = Image::new(self.width, self.height, channels);
+        let mut input = Vec::with_capacity(self.inputs());
+
+        for (row, &y) in axis(self.height).iter().enumerate() {
+            for (col, &x) in axis(self.width).iter().enumerate() {
+                input.clear();
+                input.extend_from_slice(&[x, y]);
+
+                if self.distance {
+                    input.push((x * x + y * y).sqrt());
+                }
+
+                if self.bias {
+                    input.push(1.);
+                }
+
+                let output = genome.feed_forward_all(&input)?;
+                let start = (row * self.width + col) * channels;
+
+                for (pixel, &val) in image.pixels[start..start + channels]
+                    .iter_mut()
+                    .zip(output.iter())
+                {
+                    *pixel = to_byte(val);
+                }
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+// The weights leaving the point at (row, col) of a Substrate's input plane,
+// laid out like its output plane. Positive weights are red and negative ones
+// blue, brighter the larger they are relative to the largest one.
+pub fn render_connectivity(
+    substrate: &Substrate,
+    from: (usize, usize),
+) -> Result<Image, HyperDarwinError> {
+    if from.0 >= substrate.m || from.1 >= substrate.n {
+        return Err(HyperDarwinError::PointOutOfPlane {
+            point: from,
+            plane: (substrate.m, substrate.n),
+        });
+    }
+
+    let weights = substrate.outgoing(from);
+    let largest = weights
+        .iter()
+        .flatten()
+        .fold(0., |max: f64, w| max.max(w.abs()));
+
+    let mut image = Image::new(substrate.n, substrate.m, 3);
+
+    for (i, &w) in weights.iter().flatten().enumerate() {
+        let level = if largest > 0. { w / largest } else { 0. };
+        let bright = (level.abs() * 255.).round() as u8;
+
+        if level > 0. {
+            image.pixels[i * 3] = bright;
+        } else {
+            image.pixels[i * 3 + 2] = bright;
+        }
+    }
+
+    Ok(image)
+}
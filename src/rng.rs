@@ -0,0 +1,40 @@
+use rand::rngs::StdRng;
+use rand::{Error, RngCore, SeedableRng};
+
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+// Handle to the generator behind every random choice the crate makes. Each
+// thread has its own, seeded from entropy until `seed` is called.
+#[derive(Debug, Copy, Clone)]
+pub struct DarwinRng;
+
+pub fn thread_rng() -> DarwinRng {
+    DarwinRng
+}
+
+// Makes everything random on the calling thread reproducible
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+impl RngCore for DarwinRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
@@ -1,36 +1,151 @@
+use crate::activations::ActivationSet;
+use crate::config::SettingsError;
+use crate::distance::{self, CompatibilityDistance, NeatDistance};
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Encoding {
+    Cppn,                                 // HyperNEAT CPPNs with 4 (or 6) inputs and 1 output
+    Direct { inputs: u32, outputs: u32 }, // Plain NEAT networks
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FitnessMode {
+    Objective,
+    Novelty,
+    Hybrid(f64), // Weight given to novelty, the rest goes to objective fitness
+    MultiObjective,
+}
+
+// Which Genomes a Genome's Pareto rank is computed against
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParetoScope {
+    Population, // Fronts span the whole population, as in NSGA-II
+    Species,    // Each species is ranked on its own
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RepresentativeStrategy {
+    Founder,
+    Champion,
+    Random,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InnovationTracking {
+    Run,        // Identical mutations share innovation numbers for the whole run
+    Generation, // Only within the generation they happened in, as in the NEAT paper
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Speciation {
+    Threshold,                                   // First-fit against species representatives
+    KMedoids(usize),                             // Fixed number of species
+    AdaptiveKMedoids { min: usize, max: usize }, // Number of species with the best silhouette
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Settings {
     pub pop_size: u32,
     pub third_param: bool,
+    pub encoding: Encoding,
+    pub activations: ActivationSet,
 
     pub conn_mut_rate: f64,
+    pub recurrent_conn_rate: f64, // Share of added connections that are recurrent
     pub node_mut_rate: f64,
+    pub innovation_tracking: InnovationTracking,
     pub wt_mut_rate: f64,
     pub wt_shift_rate: f64,
 
     pub off_gene_on_rate: f64,
     pub off_in_both_on_rate: f64,
     pub only_mut_rate: f64,
+    pub interspecies_mate_rate: f64,
+
+    pub elitism: usize,
+    pub elitism_min_species_size: usize,
+    pub global_elitism: usize,
 
     pub disjoint_coeff: f64,
     pub excess_coeff: f64,
     pub weight_coeff: f64,
     pub activation_coeff: f64,
+    pub node_coeff: f64,
+    pub bias_coeff: f64,
+    #[serde(with = "compatibility_metric")]
+    pub compatibility: Arc<dyn CompatibilityDistance>,
+    pub speciation: Speciation,
     pub speciation_threshold: f64,
     pub allowed_stagnancy: u32,
+    pub representative: RepresentativeStrategy,
+
+    pub fitness_mode: FitnessMode,
+    pub pareto_scope: ParetoScope, // Only used with FitnessMode::MultiObjective
+    pub novelty_k: usize,
+    pub novelty_threshold: f64,
+    pub novelty_archive_size: usize,
+    // The threshold is raised by `novelty_threshold_raise` when more than
+    // `novelty_max_additions` behaviors get archived in a generation, and lowered
+    // by `novelty_threshold_decay` after `novelty_stale_generations` without any
+    pub novelty_max_additions: usize,
+    pub novelty_threshold_raise: f64,
+    pub novelty_stale_generations: u32,
+    pub novelty_threshold_decay: f64,
+
+    pub hall_of_fame_size: usize,
+    pub track_lineage: bool,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct HyperSettings {
     pub min_weight: f64,
     pub max_weight: f64,
 }
 
-impl HyperSettings {
-    pub fn default() -> Self {
+impl Default for HyperSettings {
+    fn default() -> Self {
         Self {
             min_weight: 0.2,
             max_weight: 3.0,
         }
     }
+}
+
+impl HyperSettings {
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::<SettingsError>::new();
+
+        if self.min_weight < 0. || self.min_weight.is_nan() {
+            errors.push(SettingsError::Negative {
+                field: "min_weight",
+                value: self.min_weight,
+            });
+        }
+
+        let ordered = self.min_weight.partial_cmp(&self.max_weight) == Some(Ordering::Less);
+
+        if !ordered || !self.max_weight.is_finite() {
+            errors.push(SettingsError::InvalidWeightRange {
+                min_weight: self.min_weight,
+                max_weight: self.max_weight,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 
     pub fn min_weight(mut self, w: f64) -> Self {
         self.min_weight = w;
@@ -59,27 +174,212 @@ impl HyperSettings {
     }
 }
 
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new(150)
+    }
+}
+
 impl Settings {
     pub fn new(pop_size: u32) -> Self {
         Self {
             pop_size,
             third_param: false,
+            encoding: Encoding::Cppn,
+            activations: ActivationSet::Cppn,
             conn_mut_rate: 0.05,
+            recurrent_conn_rate: 0.,
             node_mut_rate: 0.03,
+            innovation_tracking: InnovationTracking::Run,
             wt_mut_rate: 0.8,
             wt_shift_rate: 0.9,
             off_gene_on_rate: 0.25,
             off_in_both_on_rate: 0.01,
             only_mut_rate: 0.25,
+            interspecies_mate_rate: 0.001,
+            elitism: 1,
+            elitism_min_species_size: 4,
+            global_elitism: 0,
             disjoint_coeff: 1.,
             excess_coeff: 1.,
             activation_coeff: 1.,
+            node_coeff: 1.,
+            bias_coeff: 0.5,
+            compatibility: Arc::new(NeatDistance),
             weight_coeff: 0.4,
+            speciation: Speciation::Threshold,
             speciation_threshold: 3.,
             allowed_stagnancy: 15,
+            representative: RepresentativeStrategy::Founder,
+            fitness_mode: FitnessMode::Objective,
+            pareto_scope: ParetoScope::Population,
+            novelty_k: 15,
+            novelty_threshold: 1.,
+            novelty_archive_size: 1000,
+            novelty_max_additions: 4,
+            novelty_threshold_raise: 1.2,
+            novelty_stale_generations: 5,
+            novelty_threshold_decay: 0.95,
+            hall_of_fame_size: 10,
+            track_lineage: false,
         }
     }
 
+    // Checks every setting and reports all the invalid ones at once
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::<SettingsError>::new();
+
+        if self.pop_size == 0 {
+            errors.push(SettingsError::NotPositive {
+                field: "pop_size",
+                value: 0.,
+            });
+        }
+
+        if let Encoding::Direct { inputs, outputs } = self.encoding {
+            if inputs == 0 {
+                errors.push(SettingsError::NotPositive {
+                    field: "encoding.inputs",
+                    value: 0.,
+                });
+            }
+
+            if outputs == 0 {
+                errors.push(SettingsError::NotPositive {
+                    field: "encoding.outputs",
+                    value: 0.,
+                });
+            }
+        }
+
+        if self.activations == ActivationSet::Custom(Vec::new()) {
+            errors.push(SettingsError::EmptyActivationSet);
+        }
+
+        let rates = [
+            ("conn_mut_rate", self.conn_mut_rate),
+            ("recurrent_conn_rate", self.recurrent_conn_rate),
+            ("node_mut_rate", self.node_mut_rate),
+            ("wt_mut_rate", self.wt_mut_rate),
+            ("wt_shift_rate", self.wt_shift_rate),
+            ("off_gene_on_rate", self.off_gene_on_rate),
+            ("off_in_both_on_rate", self.off_in_both_on_rate),
+            ("only_mut_rate", self.only_mut_rate),
+            ("interspecies_mate_rate", self.interspecies_mate_rate),
+            ("novelty_threshold_decay", self.novelty_threshold_decay),
+        ];
+
+        for &(field, value) in rates.iter() {
+            if !(0. ..=1.).contains(&value) {
+                errors.push(SettingsError::RateOutOfRange { field, value });
+            }
+        }
+
+        if let FitnessMode::Hybrid(weight) = self.fitness_mode {
+            if !(0. ..=1.).contains(&weight) {
+                errors.push(SettingsError::RateOutOfRange {
+                    field: "fitness_mode.Hybrid",
+                    value: weight,
+                });
+            }
+        }
+
+        let coefficients = [
+            ("disjoint_coeff", self.disjoint_coeff),
+            ("excess_coeff", self.excess_coeff),
+            ("weight_coeff", self.weight_coeff),
+            ("activation_coeff", self.activation_coeff),
+            ("node_coeff", self.node_coeff),
+            ("bias_coeff", self.bias_coeff),
+            ("novelty_threshold", self.novelty_threshold),
+            ("novelty_threshold_raise", self.novelty_threshold_raise),
+        ];
+
+        for &(field, value) in coefficients.iter() {
+            if value < 0. || value.is_nan() {
+                errors.push(SettingsError::Negative { field, value });
+            }
+        }
+
+        if self.speciation_threshold <= 0. || self.speciation_threshold.is_nan() {
+            errors.push(SettingsError::NotPositive {
+                field: "speciation_threshold",
+                value: self.speciation_threshold,
+            });
+        }
+
+        match self.speciation {
+            Speciation::Threshold => {}
+            Speciation::KMedoids(k) => {
+                if k == 0 {
+                    errors.push(SettingsError::NotPositive {
+                        field: "speciation.KMedoids",
+                        value: 0.,
+                    });
+                }
+            }
+            Speciation::AdaptiveKMedoids { min, max } => {
+                if min == 0 || min > max {
+                    errors.push(SettingsError::InvalidSpeciesRange { min, max });
+                }
+            }
+        }
+
+        // Elites alone can't fill the population, some offspring are needed
+        let elites = [
+            ("elitism", self.elitism),
+            ("global_elitism", self.global_elitism),
+        ];
+
+        for &(field, elites) in elites.iter() {
+            if elites >= self.pop_size as usize && self.pop_size > 0 {
+                errors.push(SettingsError::TooManyElites { field, elites });
+            }
+        }
+
+        let counts = [
+            ("elitism_min_species_size", self.elitism_min_species_size),
+            ("novelty_k", self.novelty_k),
+        ];
+
+        for &(field, count) in counts.iter() {
+            if count == 0 {
+                errors.push(SettingsError::NotPositive { field, value: 0. });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn genome_inputs(&self) -> u32 {
+        match self.encoding {
+            Encoding::Cppn if self.third_param => 6,
+            Encoding::Cppn => 4,
+            Encoding::Direct { inputs, .. } => inputs,
+        }
+    }
+
+    pub fn genome_outputs(&self) -> u32 {
+        match self.encoding {
+            Encoding::Cppn => 1,
+            Encoding::Direct { outputs, .. } => outputs,
+        }
+    }
+
+    pub fn direct(mut self, inputs: u32, outputs: u32) -> Self {
+        self.encoding = Encoding::Direct { inputs, outputs };
+        self
+    }
+
+    pub fn activations(mut self, activations: ActivationSet) -> Self {
+        self.activations = activations;
+        self
+    }
+
     pub fn third_param(mut self, tp: bool) -> Self {
         self.third_param = tp;
         self
@@ -90,11 +390,21 @@ impl Settings {
         self
     }
 
+    pub fn recurrent_conn_rate(mut self, rate: f64) -> Self {
+        self.recurrent_conn_rate = rate;
+        self
+    }
+
     pub fn node_mut_rate(mut self, rate: f64) -> Self {
         self.node_mut_rate = rate;
         self
     }
 
+    pub fn innovation_tracking(mut self, tracking: InnovationTracking) -> Self {
+        self.innovation_tracking = tracking;
+        self
+    }
+
     pub fn wt_mut_rate(mut self, rate: f64) -> Self {
         self.wt_mut_rate = rate;
         self
@@ -120,6 +430,26 @@ impl Settings {
         self
     }
 
+    pub fn interspecies_mate_rate(mut self, rate: f64) -> Self {
+        self.interspecies_mate_rate = rate;
+        self
+    }
+
+    pub fn elitism(mut self, elites: usize) -> Self {
+        self.elitism = elites;
+        self
+    }
+
+    pub fn elitism_min_species_size(mut self, size: usize) -> Self {
+        self.elitism_min_species_size = size;
+        self
+    }
+
+    pub fn global_elitism(mut self, elites: usize) -> Self {
+        self.global_elitism = elites;
+        self
+    }
+
     pub fn disjoint_coeff(mut self, coeff: f64) -> Self {
         self.disjoint_coeff = coeff;
         self
@@ -135,11 +465,34 @@ impl Settings {
         self
     }
 
+    pub fn node_coeff(mut self, coeff: f64) -> Self {
+        self.node_coeff = coeff;
+        self
+    }
+
+    pub fn bias_coeff(mut self, coeff: f64) -> Self {
+        self.bias_coeff = coeff;
+        self
+    }
+
+    pub fn compatibility<D>(mut self, distance: D) -> Self
+    where
+        D: CompatibilityDistance + 'static,
+    {
+        self.compatibility = Arc::new(distance);
+        self
+    }
+
     pub fn weight_coeff(mut self, coeff: f64) -> Self {
         self.weight_coeff = coeff;
         self
     }
 
+    pub fn speciation(mut self, speciation: Speciation) -> Self {
+        self.speciation = speciation;
+        self
+    }
+
     pub fn speciation_threshold(mut self, threshold: f64) -> Self {
         self.speciation_threshold = threshold;
         self
@@ -149,4 +502,89 @@ impl Settings {
         self.allowed_stagnancy = stagnancy;
         self
     }
+
+    pub fn representative(mut self, strategy: RepresentativeStrategy) -> Self {
+        self.representative = strategy;
+        self
+    }
+
+    pub fn fitness_mode(mut self, mode: FitnessMode) -> Self {
+        self.fitness_mode = mode;
+        self
+    }
+
+    pub fn pareto_scope(mut self, scope: ParetoScope) -> Self {
+        self.pareto_scope = scope;
+        self
+    }
+
+    pub fn novelty_k(mut self, k: usize) -> Self {
+        self.novelty_k = k;
+        self
+    }
+
+    pub fn novelty_threshold(mut self, threshold: f64) -> Self {
+        self.novelty_threshold = threshold;
+        self
+    }
+
+    pub fn novelty_archive_size(mut self, size: usize) -> Self {
+        self.novelty_archive_size = size;
+        self
+    }
+
+    pub fn novelty_max_additions(mut self, additions: usize) -> Self {
+        self.novelty_max_additions = additions;
+        self
+    }
+
+    pub fn novelty_threshold_raise(mut self, factor: f64) -> Self {
+        self.novelty_threshold_raise = factor;
+        self
+    }
+
+    pub fn novelty_stale_generations(mut self, generations: u32) -> Self {
+        self.novelty_stale_generations = generations;
+        self
+    }
+
+    pub fn novelty_threshold_decay(mut self, factor: f64) -> Self {
+        self.novelty_threshold_decay = factor;
+        self
+    }
+
+    pub fn hall_of_fame_size(mut self, size: usize) -> Self {
+        self.hall_of_fame_size = size;
+        self
+    }
+
+    pub fn track_lineage(mut self, track: bool) -> Self {
+        self.track_lineage = track;
+        self
+    }
+}
+
+// Compatibility metrics are written to configuration files by name
+mod compatibility_metric {
+    use super::*;
+
+    pub fn serialize<S>(
+        metric: &Arc<dyn CompatibilityDistance>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(metric.name())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Arc<dyn CompatibilityDistance>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+
+        distance::by_name(&name)
+            .ok_or_else(|| de::Error::unknown_variant(&name, &["neat", "node_gene", "parameter"]))
+    }
 }
@@ -1,25 +1,46 @@
+use crate::evolvable::Evolvable;
 use crate::genome::Genome;
-use crate::settings::Settings;
+use crate::pareto;
+use crate::rng::thread_rng;
+use crate::settings::{FitnessMode, RepresentativeStrategy, Settings};
 
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use std::cmp::Ordering;
 use std::fmt;
 use std::vec::Vec;
 
-pub struct Species {
-    pub genomes: Vec<Genome>,
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeciesSnapshot {
+    pub generation: u64,
+    pub size: usize,
+    #[serde(with = "crate::finite")]
+    pub max_fitness: f64,
+    #[serde(with = "crate::finite")]
+    pub avg_fitness: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Species<G = Genome> {
+    pub id: usize,
+    birth_generation: u64,
+    history: Vec<SpeciesSnapshot>,
+    pub genomes: Vec<G>,
+    #[serde(with = "crate::finite")]
     max_fitness: f64,
+    #[serde(with = "crate::finite")]
     pub avg_fitness: f64,
     pub stagnancy: u32,
-    representative: Genome,
+    representative: G,
     pub assigned_offspring: usize,
 }
 
-impl fmt::Debug for Species {
+impl<G: fmt::Debug> fmt::Debug for Species<G> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut res = String::from(&format!("Genomes: {}\n", self.genomes.len()));
+        let mut res = String::from(&format!("Species {}\n", self.id));
+        res += &format!("Genomes: {}\n", self.genomes.len());
         res += &format!("max_fitness: {}\n", self.max_fitness);
         res += &format!("representative: {:?}", self.representative);
 
@@ -27,125 +48,115 @@ impl fmt::Debug for Species {
     }
 }
 
-impl Species {
-    pub fn new(head: Genome) -> Self {
-        let max_fitness = head.fitness;
-        let avg_fitness = head.fitness;
+impl<G: Evolvable> Species<G> {
+    pub fn new(id: usize, head: G, generation: u64) -> Self {
+        let max_fitness = head.objective_fitness();
+        let avg_fitness = head.fitness();
         let repr = head.clone();
 
-        let species = Self {
+        Self {
+            id,
+            birth_generation: generation,
+            history: Vec::new(),
             genomes: vec![head],
             max_fitness,
             avg_fitness,
             stagnancy: 0,
             representative: repr,
             assigned_offspring: 0,
-        };
+        }
+    }
 
-        species
+    pub fn birth_generation(&self) -> u64 {
+        self.birth_generation
     }
 
-    pub fn can_accomodate(&self, gen: &Genome, sets: &Settings) -> bool {
-        let repr_genes = &self.representative.conns;
-        let new_genes = &gen.conns;
+    pub fn history(&self) -> &[SpeciesSnapshot] {
+        &self.history
+    }
 
-        let max_repr = repr_genes.iter().max_by_key(|g| g.innov).unwrap();
-        let max_new = new_genes.iter().max_by_key(|g| g.innov).unwrap();
+    pub fn representative(&self) -> &G {
+        &self.representative
+    }
 
-        let (mut genes1, mut genes2) = if max_repr.innov > max_new.innov {
-            (repr_genes.iter(), new_genes.iter())
-        } else {
-            (new_genes.iter(), repr_genes.iter())
-        };
+    pub fn max_fitness(&self) -> f64 {
+        self.max_fitness
+    }
 
-        let mut p1 = genes1.next();
-        let mut p2 = genes2.next();
-
-        let mut disjoint_genes = 0.;
-        let mut excess_genes = 0.;
-        let mut weight_difference = 0.;
-        let mut matching_genes = 0.;
-        let mut differing_activs = 0.;
-
-        while p1.is_some() {
-            if let (Some(g1), Some(g2)) = (p1, p2) {
-                if g1.innov == g2.innov {
-                    matching_genes += 1.;
-                    weight_difference += (g1.weight - g2.weight).abs();
-                    p1 = genes1.next();
-                    p2 = genes2.next();
-                } else if g2.innov > g1.innov {
-                    disjoint_genes += 1.;
-                    p1 = genes1.next();
-                } else {
-                    disjoint_genes += 1.;
-                    p2 = genes2.next();
-                }
-
-                continue;
-            }
+    pub(crate) fn set_representative(&mut self, repr: G) {
+        self.representative = repr;
+    }
 
-            if let (Some(_), None) = (p1, p2) {
-                excess_genes += 1.;
-                p1 = genes1.next();
-            }
+    // Snapshots the objective fitness, before novelty, Pareto ranking or
+    // fitness sharing rewrite it
+    pub fn record_history(&mut self, generation: u64) {
+        if self.genomes.is_empty() {
+            return;
         }
 
-        'outer: for repr_node in &self.representative.nodes {
-            for new_node in &gen.nodes {
-                if repr_node.innov == new_node.innov {
-                    if repr_node.activation != new_node.activation {
-                        differing_activs += 1.;
-                    }
-
-                    continue 'outer;
-                }
-            }
-        }
+        let fitnesses = self.genomes.iter().map(|g| g.objective_fitness());
 
-        if matching_genes == 0. {
-            return false;
-        }
+        self.history.push(SpeciesSnapshot {
+            generation,
+            size: self.genomes.len(),
+            max_fitness: fitnesses.clone().fold(f64::MIN, f64::max),
+            avg_fitness: fitnesses.sum::<f64>() / self.genomes.len() as f64,
+        });
+    }
 
-        let mut n = if repr_genes.len() > new_genes.len() {
-            repr_genes.len() as f64
-        } else {
-            new_genes.len() as f64
+    // Expects the genomes to be sorted
+    pub fn update_representative(&mut self, strategy: RepresentativeStrategy) {
+        let new_repr = match strategy {
+            RepresentativeStrategy::Founder => return,
+            RepresentativeStrategy::Champion => self.genomes.first(),
+            RepresentativeStrategy::Random => self.genomes.choose(&mut thread_rng()),
         };
 
-        n = if n < 20. { 1. } else { n };
-
-        let delta = (sets.disjoint_coeff * disjoint_genes / n)
-            + (sets.excess_coeff * excess_genes / n)
-            + (sets.weight_coeff * weight_difference / matching_genes)
-            + (sets.activation_coeff * differing_activs / n);
+        if let Some(repr) = new_repr {
+            self.representative = repr.clone();
+        }
+    }
 
-        delta < sets.speciation_threshold
+    pub fn can_accomodate(&self, gen: &G, sets: &Settings) -> bool {
+        self.representative.distance(gen, sets) < sets.speciation_threshold
     }
 
     pub fn fitness_sharing(&mut self) {
+        if self.genomes.is_empty() {
+            self.avg_fitness = 0.;
+            return;
+        }
+
         let len = self.genomes.len() as f64;
 
         self.genomes
             .iter_mut()
-            .for_each(|g| g.fitness = g.fitness / len);
+            .for_each(|g| g.set_fitness(g.fitness() / len));
 
-        let total_fitness = self.genomes.iter().fold(0., |acc, g| acc + g.fitness);
+        let total_fitness = self.genomes.iter().fold(0., |acc, g| acc + g.fitness());
 
         self.avg_fitness = total_fitness / len;
     }
 
-    fn select_parent(&self) -> &Genome {
-        let total_fitness = self.genomes.iter().fold(0., |acc, g| acc + g.fitness);
+    fn select_parent(&self, sets: &Settings) -> &G {
+        if sets.fitness_mode == FitnessMode::MultiObjective {
+            return self.tournament_parent();
+        }
+
+        let total_fitness = self.genomes.iter().fold(0., |acc, g| acc + g.fitness());
 
         let mut rng = thread_rng();
 
+        if total_fitness <= 0. {
+            return self.genomes.choose(&mut rng).unwrap();
+        }
+
         let threshold = rng.gen_range(0., total_fitness);
 
         let mut current = 0.;
 
         for genome in &self.genomes {
-            current += genome.fitness;
+            current += genome.fitness();
             if current > threshold {
                 return genome;
             }
@@ -154,32 +165,61 @@ impl Species {
         &self.genomes[0]
     }
 
-    pub fn produce_offspring(&self, amt: usize, sets: &Settings) -> Vec<Genome> {
+    // Binary tournament on the crowded comparison operator
+    fn tournament_parent(&self) -> &G {
         let mut rng = thread_rng();
 
-        let mut offspring = Vec::<Genome>::with_capacity(amt);
+        let a = self.genomes.choose(&mut rng).unwrap();
+        let b = self.genomes.choose(&mut rng).unwrap();
+
+        if pareto::crowded_cmp(a, b) == Ordering::Greater {
+            b
+        } else {
+            a
+        }
+    }
+
+    pub fn produce_offspring(&self, amt: usize, others: &[&Species<G>], sets: &Settings) -> Vec<G> {
+        let mut rng = thread_rng();
+
+        let mut offspring = Vec::<G>::with_capacity(amt);
+
+        if self.genomes.is_empty() {
+            return offspring;
+        }
 
         for _ in 0..amt {
             if rng.gen::<f64>() < sets.only_mut_rate {
-                offspring.push(self.genomes.choose(&mut rng).unwrap().clone())
+                offspring.push(self.genomes.choose(&mut rng).unwrap().offspring())
             } else {
-                let parent1 = self.select_parent();
-                let parent2 = self.select_parent();
-
-                offspring.push(Genome::crossover(parent1, parent2, sets));
+                let parent1 = self.select_parent(sets);
+                let parent2 =
+                    if !others.is_empty() && rng.gen::<f64>() < sets.interspecies_mate_rate {
+                        others.choose(&mut rng).unwrap().select_parent(sets)
+                    } else {
+                        self.select_parent(sets)
+                    };
+
+                offspring.push(G::crossover(parent1, parent2, sets));
             }
         }
 
         offspring
     }
 
+    // Stagnancy follows the objective fitness, like the history, so novelty
+    // or Pareto ranks can't reset it
     pub fn update_stagnancy(&mut self) {
-        if self.genomes.len() == 0 {
+        if self.genomes.is_empty() {
             self.stagnancy = u32::MAX;
             return;
         }
 
-        let fitness = self.genomes[0].fitness;
+        let fitness = self
+            .genomes
+            .iter()
+            .map(|g| g.objective_fitness())
+            .fold(f64::MIN, f64::max);
 
         if fitness <= self.max_fitness {
             self.stagnancy += 1;
@@ -193,17 +233,16 @@ impl Species {
         let len = self.genomes.len();
 
         if len > 2 {
-            self.genomes
-                .truncate(if len % 2 == 0 { len / 2 } else { len / 2 + 1 });
+            self.genomes.truncate(len.div_ceil(2));
         }
     }
 
-    pub fn add_genome(&mut self, gen: Genome) {
+    pub fn add_genome(&mut self, gen: G) {
         self.genomes.push(gen);
     }
 
     pub fn sort_genomes(&mut self) {
         self.genomes
-            .sort_unstable_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+            .sort_unstable_by(|a, b| b.fitness().total_cmp(&a.fitness()));
     }
 }
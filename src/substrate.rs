@@ -0,0 +1,288 @@
+use crate::activations::Activations;
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::hyper_tensor::HyperTensor;
+use crate::settings::HyperSettings;
+
+use std::vec::Vec;
+
+// Evenly spaced co-ordinates of `len` points spanning [-1, 1]
+pub(crate) fn coordinates(len: usize) -> Vec<f64> {
+    let step = 2. / (len - 1) as f64;
+
+    (0..len).map(|v| -1. + v as f64 * step).collect()
+}
+
+fn check_cppn(genome: &Genome, third_param: bool) -> Result<(), HyperDarwinError> {
+    match (genome.inputs(), third_param) {
+        (4, true) => Err(HyperDarwinError::UnexpectedThirdParamFn),
+        (6, false) => Err(HyperDarwinError::MissingThirdParamFn),
+        (4, false) | (6, true) => Ok(()),
+        (actual, _) => Err(HyperDarwinError::InputSizeMismatch {
+            expected: if third_param { 6 } else { 4 },
+            actual: actual as usize,
+        }),
+    }
+}
+
+// Weight of the connection between two points, as drawn by the CPPN
+fn query<T>(
+    genome: &Genome,
+    sets: &HyperSettings,
+    third_param_fn: &Option<T>,
+    (x1, y1): (f64, f64),
+    (x2, y2): (f64, f64),
+) -> Result<f64, HyperDarwinError>
+where
+    T: Fn(f64, f64) -> f64,
+{
+    let inp = match third_param_fn {
+        Some(tpfn) => vec![x1, y1, tpfn(x1, y1), x2, y2, tpfn(x2, y2)],
+        None => vec![x1, y1, x2, y2],
+    };
+
+    Ok(sets.scaled_weight(genome.feed_forward(&inp)?))
+}
+
+// Activates the substrate of `input`'s shape without storing its weights,
+// querying the CPPN only for the input points that are non-zero. Cheaper than
+// a Substrate when the weights are only used once.
+pub(crate) fn activate_once<T>(
+    genome: &Genome,
+    input: &HyperTensor,
+    sets: &HyperSettings,
+    third_param_fn: Option<T>,
+) -> Result<Vec<Vec<f64>>, HyperDarwinError>
+where
+    T: Fn(f64, f64) -> f64,
+{
+    check_cppn(genome, third_param_fn.is_some())?;
+
+    let x_dir = coordinates(input.n as usize);
+    let y_dir = coordinates(input.m as usize);
+
+    let mut res = vec![vec![0.; x_dir.len()]; y_dir.len()];
+
+    for (&y1, row_inp) in y_dir.iter().zip(input.values.iter()) {
+        for (&x1, &val_inp) in x_dir.iter().zip(row_inp.iter()) {
+            if val_inp == 0. {
+                continue;
+            }
+
+            for (&y2, row_out) in y_dir.iter().zip(res.iter_mut()) {
+                for (&x2, val_out) in x_dir.iter().zip(row_out.iter_mut()) {
+                    *val_out += query(genome, sets, &third_param_fn, (x1, y1), (x2, y2))? * val_inp;
+                }
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+// Two m×n planes within [-1, 1]² with every point of the input plane connected
+// to every point of the output plane, the weights queried from a CPPN once so
+// the network can be activated any number of times
+pub struct Substrate {
+    pub m: usize,
+    pub n: usize,
+    weights: Vec<f64>, // Row-major, one row of m * n weights per input point
+}
+
+impl Substrate {
+    pub fn decode<T>(
+        genome: &Genome,
+        m: usize,
+        n: usize,
+        sets: &HyperSettings,
+        third_param_fn: Option<T>,
+    ) -> Result<Self, HyperDarwinError>
+    where
+        T: Fn(f64, f64) -> f64,
+    {
+        check_cppn(genome, third_param_fn.is_some())?;
+
+        if m < 2 || n < 2 {
+            return Err(HyperDarwinError::InvalidTensorShape { rows: m, cols: n });
+        }
+
+        let x_dir = coordinates(n);
+        let y_dir = coordinates(m);
+
+        let points = y_dir
+            .iter()
+            .flat_map(|&y| x_dir.iter().map(move |&x| (x, y)))
+            .collect::<Vec<(f64, f64)>>();
+
+        let mut weights = Vec::with_capacity(points.len() * points.len());
+
+        for &(x1, y1) in &points {
+            for &(x2, y2) in &points {
+                weights.push(query(genome, sets, &third_param_fn, (x1, y1), (x2, y2))?);
+            }
+        }
+
+        Ok(Self { m, n, weights })
+    }
+
+    // Weight from the point at (row, col) of the input plane to the point at
+    // (row, col) of the output plane
+    pub fn weight(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let size = self.m * self.n;
+
+        self.weights[(from.0 * self.n + from.1) * size + to.0 * self.n + to.1]
+    }
+
+    // Weights leaving the point at (row, col) of the input plane, laid out
+    // like the output plane
+    pub fn outgoing(&self, from: (usize, usize)) -> Vec<Vec<f64>> {
+        let size = self.m * self.n;
+        let start = (from.0 * self.n + from.1) * size;
+
+        self.weights[start..start + size]
+            .chunks(self.n)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    pub fn activate(&self, input: &HyperTensor) -> Result<Vec<Vec<f64>>, HyperDarwinError> {
+        if input.m as usize != self.m || input.n as usize != self.n {
+            return Err(HyperDarwinError::InputSizeMismatch {
+                expected: self.m * self.n,
+                actual: (input.m * input.n) as usize,
+            });
+        }
+
+        let size = self.m * self.n;
+        let mut res = vec![0.; size];
+
+        for (i, &val_inp) in input.values.iter().flatten().enumerate() {
+            if val_inp == 0. {
+                continue;
+            }
+
+            let weights = &self.weights[i * size..(i + 1) * size];
+
+            for (val_out, weight) in res.iter_mut().zip(weights.iter()) {
+                *val_out += weight * val_inp;
+            }
+        }
+
+        Ok(res.chunks(self.n).map(|row| row.to_vec()).collect())
+    }
+}
+
+// A feed-forward network with its neurons placed in layers on the plane and
+// every layer fully connected to the next one. Unlike a Substrate the layers
+// may have any size and position, and every neuron past the inputs applies
+// `activation` to its weighted sum plus a bias. The CPPN draws the bias of a
+// neuron as the weight of a connection from the neuron to itself.
+pub struct Network {
+    layers: Vec<Vec<(f64, f64)>>,
+    weights: Vec<Vec<f64>>, // Row-major, one matrix per pair of consecutive layers
+    biases: Vec<Vec<f64>>,  // One per neuron past the inputs
+    pub activation: Activations,
+}
+
+impl Network {
+    // `len` neurons spread evenly across x at height `y`, or a single one at x = 0
+    pub fn row(len: usize, y: f64) -> Vec<(f64, f64)> {
+        match len {
+            1 => vec![(0., y)],
+            _ => coordinates(len).into_iter().map(|x| (x, y)).collect(),
+        }
+    }
+
+    pub fn decode<T>(
+        genome: &Genome,
+        layers: Vec<Vec<(f64, f64)>>,
+        sets: &HyperSettings,
+        third_param_fn: Option<T>,
+    ) -> Result<Self, HyperDarwinError>
+    where
+        T: Fn(f64, f64) -> f64,
+    {
+        check_cppn(genome, third_param_fn.is_some())?;
+
+        if layers.len() < 2 || layers.iter().any(|layer| layer.is_empty()) {
+            return Err(HyperDarwinError::InvalidLayers(
+                layers.iter().map(|layer| layer.len()).collect(),
+            ));
+        }
+
+        let mut weights = Vec::with_capacity(layers.len() - 1);
+
+        for pair in layers.windows(2) {
+            let mut matrix = Vec::with_capacity(pair[0].len() * pair[1].len());
+
+            for &from in &pair[0] {
+                for &to in &pair[1] {
+                    matrix.push(query(genome, sets, &third_param_fn, from, to)?);
+                }
+            }
+
+            weights.push(matrix);
+        }
+
+        let biases = layers[1..]
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|&point| query(genome, sets, &third_param_fn, point, point))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            layers,
+            weights,
+            biases,
+            activation: Activations::Sigmoid,
+        })
+    }
+
+    pub fn activation(mut self, activation: Activations) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    pub fn layers(&self) -> &[Vec<(f64, f64)>] {
+        &self.layers
+    }
+
+    // Weight from neuron `from` of `layer` to neuron `to` of the next layer
+    pub fn weight(&self, layer: usize, from: usize, to: usize) -> f64 {
+        self.weights[layer][from * self.layers[layer + 1].len() + to]
+    }
+
+    // Bias of neuron `neuron` of `layer`, which can't be the input layer
+    pub fn bias(&self, layer: usize, neuron: usize) -> f64 {
+        self.biases[layer - 1][neuron]
+    }
+
+    pub fn activate(&self, input: &[f64]) -> Result<Vec<f64>, HyperDarwinError> {
+        if input.len() != self.layers[0].len() {
+            return Err(HyperDarwinError::InputSizeMismatch {
+                expected: self.layers[0].len(),
+                actual: input.len(),
+            });
+        }
+
+        let mut values = input.to_vec();
+
+        for (matrix, biases) in self.weights.iter().zip(self.biases.iter()) {
+            let mut next = biases.clone();
+
+            for (&val, weights) in values.iter().zip(matrix.chunks(biases.len())) {
+                for (sum, weight) in next.iter_mut().zip(weights.iter()) {
+                    *sum += weight * val;
+                }
+            }
+
+            values = next.into_iter().map(|v| self.activation.apply(v)).collect();
+        }
+
+        Ok(values)
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::hyper_tensor::HyperTensor;
+use crate::settings::{Encoding, HyperSettings};
+use crate::substrate::{coordinates, Substrate};
+use crate::tasks::Task;
+
+use serde::{Deserialize, Serialize};
+
+use std::f64::consts::SQRT_2;
+
+// Visual discrimination from the HyperNEAT paper: a small and a large box are
+// shown on the retina and the most active point of the output plane has to
+// land on the center of the large box. The retina defaults to 11×11 with
+// boxes of side 1 and 3, and both scale with `resolution`, so a substrate
+// evolved at one resolution can be checked at another.
+//
+// Fitness is 1 minus the mean distance between the most active point and the
+// center of the large box, relative to the diagonal of the substrate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Boxes {
+    pub resolution: usize,
+    pub positions: usize, // Per axis, each position is tried with 3 offsets
+    pub target_fitness: f64,
+}
+
+// Results of every trial at one resolution
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoxesReport {
+    pub fitness: f64,
+    pub mean_distance: f64, // In substrate co-ordinates
+    pub accuracy: f64,      // Fraction of trials where the center was hit exactly
+}
+
+// Centers of the two boxes as (row, col)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoxesTrial {
+    pub small: (usize, usize),
+    pub large: (usize, usize),
+}
+
+impl Default for Boxes {
+    fn default() -> Self {
+        Self {
+            resolution: 11,
+            positions: 5,
+            target_fitness: 0.98,
+        }
+    }
+}
+
+impl Boxes {
+    pub fn resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    // Sides of the small and large boxes, always odd so each has a center point
+    pub fn box_sides(&self) -> (usize, usize) {
+        (
+            (self.resolution / 11).max(1) | 1,
+            (3 * self.resolution / 11).max(3) | 1,
+        )
+    }
+
+    // The large box visits a grid of positions and the small box sits half
+    // the retina away from it to the right, below or diagonally, folding back
+    // when it would fall off the edge
+    pub fn trials(&self) -> Vec<BoxesTrial> {
+        let res = self.resolution.max(3);
+        let (small, large) = self.box_sides();
+        let (hs, hl) = (small / 2, large / 2);
+        let offset = res / 2;
+        let steps = self.positions.max(1);
+
+        let spread = |i: usize| {
+            let span = res - 1 - 2 * hl.min((res - 1) / 2);
+
+            match steps {
+                1 => hl + span / 2,
+                _ => hl + i * span / (steps - 1),
+            }
+        };
+
+        let place = |large: usize, shift: usize| {
+            let pos = if large + shift + hs < res {
+                large + shift
+            } else {
+                large.saturating_sub(shift)
+            };
+
+            pos.clamp(hs, res - 1 - hs)
+        };
+
+        let mut trials = Vec::with_capacity(steps * steps * 3);
+
+        for row in (0..steps).map(spread) {
+            for col in (0..steps).map(spread) {
+                for &(dr, dc) in &[(0, offset), (offset, 0), (offset, offset)] {
+                    trials.push(BoxesTrial {
+                        small: (place(row, dr), place(col, dc)),
+                        large: (row, col),
+                    });
+                }
+            }
+        }
+
+        trials
+    }
+
+    pub fn retina(&self, trial: &BoxesTrial) -> Result<HyperTensor, HyperDarwinError> {
+        let res = self.resolution;
+        let (small, large) = self.box_sides();
+        let mut values = vec![vec![0.; res]; res];
+
+        for &((row, col), side) in &[(trial.small, small), (trial.large, large)] {
+            let half = side / 2;
+
+            let rows = row.saturating_sub(half)..(row + half + 1).min(res);
+            let cols = col.saturating_sub(half)..(col + half + 1).min(res);
+
+            for values_row in &mut values[rows] {
+                values_row[cols.clone()].iter_mut().for_each(|v| *v = 1.);
+            }
+        }
+
+        HyperTensor::new(values)
+    }
+
+    // Decodes the substrate at this resolution, with the distance from the
+    // center as third parameter when the CPPN takes one
+    pub fn substrate(
+        &self,
+        genome: &Genome,
+        hyper: &HyperSettings,
+    ) -> Result<Substrate, HyperDarwinError> {
+        let res = self.resolution;
+
+        if genome.inputs() == 6 {
+            let dist = |x: f64, y: f64| (x * x + y * y).sqrt();
+            Substrate::decode(genome, res, res, hyper, Some(dist))
+        } else {
+            Substrate::decode(genome, res, res, hyper, None::<fn(f64, f64) -> f64>)
+        }
+    }
+
+    pub fn report(
+        &self,
+        genome: &Genome,
+        hyper: &HyperSettings,
+    ) -> Result<BoxesReport, HyperDarwinError> {
+        if self.resolution < 3 {
+            return Err(HyperDarwinError::InvalidTensorShape {
+                rows: self.resolution,
+                cols: self.resolution,
+            });
+        }
+
+        let substrate = self.substrate(genome, hyper)?;
+        let coords = coordinates(self.resolution);
+        let trials = self.trials();
+
+        let mut distance = 0.;
+        let mut hits = 0;
+
+        for trial in &trials {
+            let output = substrate.activate(&self.retina(trial)?)?;
+
+            let mut best = (0, 0);
+
+            for (r, row) in output.iter().enumerate() {
+                for (c, &val) in row.iter().enumerate() {
+                    if val > output[best.0][best.1] {
+                        best = (r, c);
+                    }
+                }
+            }
+
+            if best == trial.large {
+                hits += 1;
+            }
+
+            let dy = coords[best.0] - coords[trial.large.0];
+            let dx = coords[best.1] - coords[trial.large.1];
+
+            distance += (dx * dx + dy * dy).sqrt();
+        }
+
+        let len = trials.len() as f64;
+        let mean_distance = distance / len;
+
+        Ok(BoxesReport {
+            fitness: 1. - mean_distance / (2. * SQRT_2),
+            mean_distance,
+            accuracy: hits as f64 / len,
+        })
+    }
+}
+
+impl Task for Boxes {
+    fn name(&self) -> &'static str {
+        "boxes"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Cppn
+    }
+
+    fn evaluate(&self, genome: &Genome, hyper: &HyperSettings) -> f64 {
+        self.report(genome, hyper)
+            .map_or(0., |report| report.fitness)
+    }
+
+    fn solved(&self, genome: &Genome, hyper: &HyperSettings) -> bool {
+        self.evaluate(genome, hyper) >= self.target_fitness
+    }
+}
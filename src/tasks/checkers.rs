@@ -0,0 +1,555 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::hyper_tensor::HyperTensor;
+use crate::settings::{Encoding, HyperSettings};
+use crate::substrate::{coordinates, Network};
+use crate::tasks::Task;
+
+use serde::{Deserialize, Serialize};
+
+const SIZE: usize = 8;
+const KING_VALUE: f64 = 1.5;
+
+// Far beyond any evaluation, less the plies it takes so quicker wins score higher
+const WIN: f64 = 1e9;
+
+const DIRECTIONS: [(isize, isize); 4] = [(1, -1), (1, 1), (-1, -1), (-1, 1)];
+
+// (row, col) with row 0 being Black's back row
+pub type Square = (usize, usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    Black, // Moves first, up from row 0
+    White,
+}
+
+impl Side {
+    pub fn opponent(self) -> Self {
+        match self {
+            Side::Black => Side::White,
+            Side::White => Side::Black,
+        }
+    }
+
+    // Row where this side's men are crowned
+    pub fn crowning_row(self) -> usize {
+        match self {
+            Side::Black => SIZE - 1,
+            Side::White => 0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Piece {
+    pub side: Side,
+    pub king: bool,
+}
+
+impl Piece {
+    fn directions(&self) -> &'static [(isize, isize)] {
+        match (self.king, self.side) {
+            (true, _) => &DIRECTIONS,
+            (false, Side::Black) => &DIRECTIONS[..2],
+            (false, Side::White) => &DIRECTIONS[2..],
+        }
+    }
+}
+
+// Squares visited from the start to the end of a move, with the squares of
+// the pieces it captures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub path: Vec<Square>,
+    pub captures: Vec<Square>,
+}
+
+fn offset((row, col): Square, (dr, dc): (isize, isize), times: isize) -> Option<Square> {
+    let row = row as isize + dr * times;
+    let col = col as isize + dc * times;
+
+    if (0..SIZE as isize).contains(&row) && (0..SIZE as isize).contains(&col) {
+        Some((row as usize, col as usize))
+    } else {
+        None
+    }
+}
+
+// The 32 playable squares, bottom row first
+pub fn playable_squares() -> Vec<Square> {
+    (0..SIZE)
+        .flat_map(|row| (0..SIZE).map(move |col| (row, col)))
+        .filter(|(row, col)| (row + col) % 2 == 0)
+        .collect()
+}
+
+// An 8×8 checkers board under the English draughts rules: men move and
+// capture diagonally forward, kings both ways, capturing is compulsory and a
+// capture carries on for as long as it can, ending when a man is crowned
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Board {
+    squares: [[Option<Piece>; SIZE]; SIZE],
+    pub to_move: Side,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    // The starting position, 12 men each with Black to move
+    pub fn new() -> Self {
+        let mut board = Self::empty(Side::Black);
+
+        for (row, col) in playable_squares() {
+            let side = match row {
+                0..=2 => Side::Black,
+                5..=7 => Side::White,
+                _ => continue,
+            };
+
+            board.set((row, col), Some(Piece { side, king: false }));
+        }
+
+        board
+    }
+
+    pub fn empty(to_move: Side) -> Self {
+        Self {
+            squares: [[None; SIZE]; SIZE],
+            to_move,
+        }
+    }
+
+    pub fn get(&self, (row, col): Square) -> Option<Piece> {
+        self.squares[row][col]
+    }
+
+    pub fn set(&mut self, (row, col): Square, piece: Option<Piece>) {
+        self.squares[row][col] = piece;
+    }
+
+    pub fn pieces(&self, side: Side) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        playable_squares()
+            .into_iter()
+            .filter_map(move |sq| self.get(sq).map(|piece| (sq, piece)))
+            .filter(move |(_, piece)| piece.side == side)
+    }
+
+    // Men count 1 and kings 1.5
+    pub fn material(&self, side: Side) -> f64 {
+        self.pieces(side)
+            .map(|(_, piece)| if piece.king { KING_VALUE } else { 1. })
+            .fold(0., |sum, value| sum + value)
+    }
+
+    // Legal moves of the side to move, only captures when there are any. A
+    // side left without moves has lost.
+    pub fn moves(&self) -> Vec<Move> {
+        let mut jumps = Vec::new();
+        let mut steps = Vec::new();
+
+        for (from, piece) in self.pieces(self.to_move) {
+            self.collect_jumps(piece, &mut vec![from], &mut Vec::new(), &mut jumps);
+
+            for &dir in piece.directions() {
+                if let Some(to) = offset(from, dir, 1).filter(|&to| self.get(to).is_none()) {
+                    steps.push(Move {
+                        path: vec![from, to],
+                        captures: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        if jumps.is_empty() {
+            steps
+        } else {
+            jumps
+        }
+    }
+
+    fn collect_jumps(
+        &self,
+        piece: Piece,
+        path: &mut Vec<Square>,
+        captures: &mut Vec<Square>,
+        jumps: &mut Vec<Move>,
+    ) {
+        let at = *path.last().unwrap();
+        let crowned = !piece.king && !captures.is_empty() && at.0 == piece.side.crowning_row();
+        let mut extended = false;
+
+        for &dir in piece.directions().iter().filter(|_| !crowned) {
+            let (over, to) = match (offset(at, dir, 1), offset(at, dir, 2)) {
+                (Some(over), Some(to)) => (over, to),
+                _ => continue,
+            };
+
+            let capturable =
+                self.get(over).is_some_and(|p| p.side != piece.side) && !captures.contains(&over);
+
+            // The moving piece has left its starting square
+            let free = self.get(to).is_none() || to == path[0];
+
+            if capturable && free {
+                path.push(to);
+                captures.push(over);
+
+                self.collect_jumps(piece, path, captures, jumps);
+
+                path.pop();
+                captures.pop();
+                extended = true;
+            }
+        }
+
+        if !extended && !captures.is_empty() {
+            jumps.push(Move {
+                path: path.clone(),
+                captures: captures.clone(),
+            });
+        }
+    }
+
+    // The board after a move taken from `moves`
+    pub fn play(&self, mv: &Move) -> Self {
+        let mut next = *self;
+        let (from, to) = (mv.path[0], *mv.path.last().unwrap());
+
+        let mut piece = next.get(from).expect("no piece to move");
+        next.set(from, None);
+
+        for &sq in &mv.captures {
+            next.set(sq, None);
+        }
+
+        if to.0 == piece.side.crowning_row() {
+            piece.king = true;
+        }
+
+        next.set(to, Some(piece));
+        next.to_move = self.to_move.opponent();
+        next
+    }
+
+    // Value of a square seen by `side`: 1 for its men, 1.5 for its kings and
+    // the negatives for the opponent's
+    fn value(&self, sq: Square, side: Side) -> f64 {
+        self.get(sq).map_or(0., |piece| {
+            let value = if piece.king { KING_VALUE } else { 1. };
+
+            if piece.side == side {
+                value
+            } else {
+                -value
+            }
+        })
+    }
+
+    // Turns the board around for White so `side` always plays up from row 0
+    fn oriented(&self, (row, col): Square, side: Side) -> Square {
+        match side {
+            Side::Black => (row, col),
+            Side::White => (SIZE - 1 - row, SIZE - 1 - col),
+        }
+    }
+
+    // The board as seen by `side` for a Substrate, light squares being 0
+    pub fn tensor(&self, side: Side) -> Result<HyperTensor, HyperDarwinError> {
+        let values = (0..SIZE)
+            .map(|row| {
+                (0..SIZE)
+                    .map(|col| self.value(self.oriented((row, col), side), side))
+                    .collect()
+            })
+            .collect();
+
+        HyperTensor::new(values)
+    }
+
+    // Values of the playable squares as seen by `side`, in the order of
+    // `playable_squares`
+    pub fn inputs(&self, side: Side) -> Vec<f64> {
+        playable_squares()
+            .into_iter()
+            .map(|sq| self.value(self.oriented(sq, side), side))
+            .collect()
+    }
+}
+
+// Scores a board for one side, higher being better for it
+pub trait Evaluator {
+    fn evaluate(&self, board: &Board, side: Side) -> f64;
+}
+
+// Material difference, the evaluation of the built-in opponent
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Material;
+
+impl Evaluator for Material {
+    fn evaluate(&self, board: &Board, side: Side) -> f64 {
+        board.material(side) - board.material(side.opponent())
+    }
+}
+
+// A Network decoded over the playable squares, see Checkers::layers
+impl Evaluator for Network {
+    fn evaluate(&self, board: &Board, side: Side) -> f64 {
+        self.activate(&board.inputs(side)).map_or(0., |out| out[0])
+    }
+}
+
+fn negamax<E: Evaluator + ?Sized>(
+    board: &Board,
+    evaluator: &E,
+    depth: usize,
+    ply: usize,
+    mut alpha: f64,
+    beta: f64,
+) -> f64 {
+    let moves = board.moves();
+
+    if moves.is_empty() {
+        return ply as f64 - WIN;
+    }
+
+    if depth == 0 {
+        return evaluator.evaluate(board, board.to_move);
+    }
+
+    let mut best = f64::NEG_INFINITY;
+
+    for mv in &moves {
+        let score = -negamax(
+            &board.play(mv),
+            evaluator,
+            depth - 1,
+            ply + 1,
+            -beta,
+            -alpha,
+        );
+
+        best = best.max(score);
+        alpha = alpha.max(score);
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+// Alpha-beta search `depth` plies ahead for the side to move, returning the
+// best move with its score. Ties go to the first move generated, so the
+// search is deterministic.
+pub fn minimax<E: Evaluator + ?Sized>(
+    board: &Board,
+    evaluator: &E,
+    depth: usize,
+) -> Option<(Move, f64)> {
+    let mut best: Option<(Move, f64)> = None;
+    let mut alpha = f64::NEG_INFINITY;
+
+    for mv in board.moves() {
+        let score = -negamax(
+            &board.play(&mv),
+            evaluator,
+            depth.max(1) - 1,
+            1,
+            f64::NEG_INFINITY,
+            -alpha,
+        );
+
+        if best.as_ref().is_none_or(|(_, best)| score > *best) {
+            alpha = score;
+            best = Some((mv, score));
+        }
+    }
+
+    best
+}
+
+// An evaluator searching a fixed number of plies
+#[derive(Clone, Copy)]
+pub struct Player<'a> {
+    pub evaluator: &'a dyn Evaluator,
+    pub depth: usize,
+}
+
+impl<'a> Player<'a> {
+    pub fn new(evaluator: &'a dyn Evaluator, depth: usize) -> Self {
+        Self { evaluator, depth }
+    }
+
+    // The simple deterministic opponent, counting material `depth` plies ahead
+    pub fn material(depth: usize) -> Self {
+        Self::new(&Material, depth)
+    }
+
+    pub fn choose(&self, board: &Board) -> Option<Move> {
+        minimax(board, self.evaluator, self.depth).map(|(mv, _)| mv)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Win(Side),
+    Draw, // Nobody won within the ply limit
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    pub outcome: Outcome,
+    pub plies: usize,
+    pub board: Board, // Final position
+}
+
+// Plays a game from the starting position
+pub fn play(black: &Player, white: &Player, max_plies: usize) -> Game {
+    let mut board = Board::new();
+
+    for ply in 0..max_plies {
+        let player = match board.to_move {
+            Side::Black => black,
+            Side::White => white,
+        };
+
+        match player.choose(&board) {
+            Some(mv) => board = board.play(&mv),
+            None => {
+                return Game {
+                    outcome: Outcome::Win(board.to_move.opponent()),
+                    plies: ply,
+                    board,
+                }
+            }
+        }
+    }
+
+    Game {
+        outcome: Outcome::Draw,
+        plies: max_plies,
+        board,
+    }
+}
+
+// Evolving a checkers board evaluator, as in Gauci and Stanley's HyperNEAT
+// checkers. The CPPN draws a Network from the 32 playable squares through
+// `hidden_layers` planes of the same squares to a single output at the center
+// of the board, and the evaluator plays a game as each side against the
+// material counting opponent. Both players search with minimax, so the games
+// are deterministic.
+//
+// A game scores 1 for a win, 0.5 for a draw and 0 for a loss, weighted 0.75,
+// plus 0.25 times the evolved side's share of the material left. Fitness is
+// the mean over both games and the task is solved by winning both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Checkers {
+    pub depth: usize,          // Plies searched by the evolved evaluator
+    pub opponent_depth: usize, // Plies searched by the material opponent
+    pub max_plies: usize,
+    pub hidden_layers: usize,
+}
+
+impl Default for Checkers {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            opponent_depth: 4,
+            max_plies: 200,
+            hidden_layers: 1,
+        }
+    }
+}
+
+impl Checkers {
+    // The playable squares at their board positions, as seen by the side to
+    // evaluate, then a hidden plane of the same positions for each hidden
+    // layer and the output at the center
+    pub fn layers(&self) -> Vec<Vec<(f64, f64)>> {
+        let coords = coordinates(SIZE);
+
+        let board: Vec<(f64, f64)> = playable_squares()
+            .into_iter()
+            .map(|(row, col)| (coords[col], coords[row]))
+            .collect();
+
+        let mut layers = vec![board.clone(); self.hidden_layers + 1];
+        layers.push(vec![(0., 0.)]);
+        layers
+    }
+
+    // Decodes the evaluator, with the distance from the center as third
+    // parameter when the CPPN takes one
+    pub fn network(
+        &self,
+        genome: &Genome,
+        hyper: &HyperSettings,
+    ) -> Result<Network, HyperDarwinError> {
+        if genome.inputs() == 6 {
+            let dist = |x: f64, y: f64| (x * x + y * y).sqrt();
+            Network::decode(genome, self.layers(), hyper, Some(dist))
+        } else {
+            Network::decode(genome, self.layers(), hyper, None::<fn(f64, f64) -> f64>)
+        }
+    }
+
+    // The games as Black then as White against the material opponent
+    pub fn games(&self, evaluator: &dyn Evaluator) -> [Game; 2] {
+        let player = Player::new(evaluator, self.depth);
+        let opponent = Player::material(self.opponent_depth);
+
+        [
+            play(&player, &opponent, self.max_plies),
+            play(&opponent, &player, self.max_plies),
+        ]
+    }
+
+    pub fn score(game: &Game, side: Side) -> f64 {
+        let result = match game.outcome {
+            Outcome::Win(winner) if winner == side => 1.,
+            Outcome::Win(_) => 0.,
+            Outcome::Draw => 0.5,
+        };
+
+        let own = game.board.material(side);
+        let total = own + game.board.material(side.opponent());
+
+        0.75 * result + 0.25 * if total > 0. { own / total } else { 0.5 }
+    }
+}
+
+impl Task for Checkers {
+    fn name(&self) -> &'static str {
+        "checkers"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Cppn
+    }
+
+    fn evaluate(&self, genome: &Genome, hyper: &HyperSettings) -> f64 {
+        let network = match self.network(genome, hyper) {
+            Ok(network) => network,
+            Err(_) => return 0.,
+        };
+
+        let [black, white] = self.games(&network);
+
+        (Self::score(&black, Side::Black) + Self::score(&white, Side::White)) / 2.
+    }
+
+    fn solved(&self, genome: &Genome, hyper: &HyperSettings) -> bool {
+        self.network(genome, hyper).is_ok_and(|network| {
+            let [black, white] = self.games(&network);
+
+            black.outcome == Outcome::Win(Side::Black) && white.outcome == Outcome::Win(Side::White)
+        })
+    }
+}
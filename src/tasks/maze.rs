@@ -0,0 +1,462 @@
+use crate::environment::{Environment, EpisodeRunner, Space, Step};
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+use crate::tasks::Task;
+
+use serde::{Deserialize, Serialize};
+
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// Name of a maze that was parsed rather than built in or loaded from a file
+const CUSTOM: &str = "custom";
+
+const MEDIUM: &str = include_str!("../../mazes/medium.txt");
+const HARD: &str = include_str!("../../mazes/hard.txt");
+
+const ROBOT_RADIUS: f64 = 8.;
+const RANGEFINDER_RANGE: f64 = 100.;
+const RANGEFINDER_ANGLES: [f64; 6] = [-90., -45., 0., 45., 90., -180.];
+const MAX_SPEED: f64 = 3.;
+
+type Point = (f64, f64);
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Wall {
+    pub from: Point,
+    pub to: Point,
+}
+
+impl Wall {
+    fn distance_to(&self, (px, py): Point) -> f64 {
+        let (dx, dy) = (self.to.0 - self.from.0, self.to.1 - self.from.1);
+        let len = dx * dx + dy * dy;
+
+        let t = if len == 0. {
+            0.
+        } else {
+            (((px - self.from.0) * dx + (py - self.from.1) * dy) / len).clamp(0., 1.)
+        };
+
+        let (cx, cy) = (self.from.0 + t * dx, self.from.1 + t * dy);
+
+        ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+    }
+
+    // Distance along a ray to this wall, if the ray hits it
+    fn intersect(&self, (ox, oy): Point, (rx, ry): Point) -> Option<f64> {
+        let (sx, sy) = (self.to.0 - self.from.0, self.to.1 - self.from.1);
+        let denom = rx * sy - ry * sx;
+
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+
+        let (qx, qy) = (self.from.0 - ox, self.from.1 - oy);
+        let t = (qx * sy - qy * sx) / denom;
+        let u = (qx * ry - qy * rx) / denom;
+
+        if t >= 0. && (0. ..=1.).contains(&u) {
+            Some(t)
+        } else {
+            None
+        }
+    }
+}
+
+// Walls, start and goal of a maze. Mazes are written one item per line, with
+// blank lines and lines starting with # ignored:
+//
+//     start <x> <y> <heading in degrees>
+//     goal <x> <y>
+//     wall <x1> <y1> <x2> <y2>
+//
+// A built-in or file-backed maze is serialized as its name, a parsed one as its
+// start, heading, goal and walls.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "MazeSource", into = "MazeSource")]
+pub struct Maze {
+    pub name: String, // Built-in name, path the maze was loaded from or "custom"
+    pub start: Point,
+    pub start_heading: f64,
+    pub goal: Point,
+    pub walls: Vec<Wall>,
+}
+
+impl Maze {
+    pub fn parse(text: &str) -> Result<Self, HyperDarwinError> {
+        let mut start = None;
+        let mut goal = None;
+        let mut walls = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |reason: &str| HyperDarwinError::InvalidMaze {
+                line: i + 1,
+                reason: reason.to_string(),
+            };
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+
+            let values = words
+                .map(f64::from_str)
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| invalid("expected numbers after the keyword"))?;
+
+            match (keyword, values.as_slice()) {
+                ("start", &[x, y, heading]) => start = Some(((x, y), heading)),
+                ("goal", &[x, y]) => goal = Some((x, y)),
+                ("wall", &[x1, y1, x2, y2]) => walls.push(Wall {
+                    from: (x1, y1),
+                    to: (x2, y2),
+                }),
+                ("start", _) => return Err(invalid("start takes x, y and a heading")),
+                ("goal", _) => return Err(invalid("goal takes x and y")),
+                ("wall", _) => return Err(invalid("wall takes x1, y1, x2 and y2")),
+                _ => return Err(invalid("expected start, goal or wall")),
+            }
+        }
+
+        let missing = |reason: &str| HyperDarwinError::InvalidMaze {
+            line: 0,
+            reason: reason.to_string(),
+        };
+
+        let (start, start_heading) = start.ok_or_else(|| missing("the maze has no start"))?;
+        let goal = goal.ok_or_else(|| missing("the maze has no goal"))?;
+
+        Ok(Self {
+            name: String::from(CUSTOM),
+            start,
+            start_heading,
+            goal,
+            walls,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, HyperDarwinError> {
+        let mut maze = Self::parse(&fs::read_to_string(&path)?)?;
+        maze.name = path.as_ref().display().to_string();
+
+        Ok(maze)
+    }
+
+    // A built-in maze by name, or else a maze file
+    pub fn named(name: &str) -> Result<Self, HyperDarwinError> {
+        let text = match name {
+            "medium" => MEDIUM,
+            "hard" => HARD,
+            path => return Self::load(path),
+        };
+
+        let mut maze = Self::parse(text)?;
+        maze.name = name.to_string();
+
+        Ok(maze)
+    }
+
+    pub fn medium() -> Self {
+        Self::named("medium").unwrap()
+    }
+
+    pub fn hard() -> Self {
+        Self::named("hard").unwrap()
+    }
+
+    // Diagonal of the box around the walls, the farthest the robot can be
+    // from the goal
+    pub fn diagonal(&self) -> f64 {
+        let points = self.walls.iter().flat_map(|w| vec![w.from, w.to]);
+
+        let (min, max) = points.fold((self.start, self.start), |((x0, y0), (x1, y1)), (x, y)| {
+            ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y)))
+        });
+
+        ((max.0 - min.0).powi(2) + (max.1 - min.1).powi(2)).sqrt()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MazeLayout {
+    start: Point,
+    start_heading: f64,
+    goal: Point,
+    walls: Vec<Wall>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum MazeSource {
+    Named(String),
+    Layout(MazeLayout),
+}
+
+impl TryFrom<MazeSource> for Maze {
+    type Error = HyperDarwinError;
+
+    fn try_from(source: MazeSource) -> Result<Self, Self::Error> {
+        match source {
+            MazeSource::Named(name) => Self::named(&name),
+            MazeSource::Layout(layout) => Ok(Self {
+                name: String::from(CUSTOM),
+                start: layout.start,
+                start_heading: layout.start_heading,
+                goal: layout.goal,
+                walls: layout.walls,
+            }),
+        }
+    }
+}
+
+impl From<Maze> for MazeSource {
+    fn from(maze: Maze) -> Self {
+        if maze.name != CUSTOM {
+            return MazeSource::Named(maze.name);
+        }
+
+        MazeSource::Layout(MazeLayout {
+            start: maze.start,
+            start_heading: maze.start_heading,
+            goal: maze.goal,
+            walls: maze.walls,
+        })
+    }
+}
+
+// A round robot with six rangefinders and four radar slices pointing it to the
+// goal, as in the novelty search experiments. Observations are the
+// rangefinder readings as fractions of their range followed by the radar
+// slices (front, left, back, right), 1 for the one containing the goal. The
+// two actions in [-1, 1] change the angular velocity and the speed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MazeNavigator {
+    pub maze: Maze,
+    pub position: Point,
+    pub heading: f64, // Degrees
+    pub speed: f64,
+    pub angular_velocity: f64,
+    pub steps: u64,
+    pub max_steps: u64,
+    pub goal_radius: f64, // Reaching this close to the goal ends the episode
+}
+
+impl MazeNavigator {
+    pub fn new(maze: Maze) -> Self {
+        Self {
+            position: maze.start,
+            heading: maze.start_heading,
+            maze,
+            speed: 0.,
+            angular_velocity: 0.,
+            steps: 0,
+            max_steps: 400,
+            goal_radius: 5.,
+        }
+    }
+
+    pub fn distance_to_goal(&self) -> f64 {
+        let (dx, dy) = (
+            self.maze.goal.0 - self.position.0,
+            self.maze.goal.1 - self.position.1,
+        );
+
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    pub fn reached_goal(&self) -> bool {
+        self.distance_to_goal() <= self.goal_radius
+    }
+
+    // 1 at the goal down to 0 at the far end of the maze
+    pub fn fitness(&self) -> f64 {
+        (1. - self.distance_to_goal() / self.maze.diagonal()).max(0.)
+    }
+
+    pub fn rangefinders(&self) -> Vec<f64> {
+        RANGEFINDER_ANGLES
+            .iter()
+            .map(|angle| {
+                let rad = (self.heading + angle).to_radians();
+                let ray = (rad.cos(), rad.sin());
+
+                self.maze
+                    .walls
+                    .iter()
+                    .filter_map(|wall| wall.intersect(self.position, ray))
+                    .fold(RANGEFINDER_RANGE, f64::min)
+                    / RANGEFINDER_RANGE
+            })
+            .collect()
+    }
+
+    pub fn radar(&self) -> Vec<f64> {
+        let (dx, dy) = (
+            self.maze.goal.0 - self.position.0,
+            self.maze.goal.1 - self.position.1,
+        );
+
+        let angle = (dy.atan2(dx).to_degrees() - self.heading).rem_euclid(360.);
+        let slice = ((angle + 45.).rem_euclid(360.) / 90.) as usize;
+
+        (0..4).map(|i| if i == slice { 1. } else { 0. }).collect()
+    }
+
+    pub fn observation(&self) -> Vec<f64> {
+        let mut obs = self.rangefinders();
+        obs.extend(self.radar());
+        obs
+    }
+
+    // Turns and moves the robot, which stays put instead of entering a wall
+    pub fn act(&mut self, action: &[f64]) {
+        self.angular_velocity =
+            (self.angular_velocity + action[0] / 2.).clamp(-MAX_SPEED, MAX_SPEED);
+        self.speed = (self.speed + action[1] / 2.).clamp(-MAX_SPEED, MAX_SPEED);
+
+        self.heading = (self.heading + self.angular_velocity).rem_euclid(360.);
+
+        let rad = self.heading.to_radians();
+        let next = (
+            self.position.0 + rad.cos() * self.speed,
+            self.position.1 + rad.sin() * self.speed,
+        );
+
+        if self
+            .maze
+            .walls
+            .iter()
+            .all(|wall| wall.distance_to(next) >= ROBOT_RADIUS)
+        {
+            self.position = next;
+        }
+
+        self.steps += 1;
+    }
+}
+
+// The fitness is the only reward, given when the episode ends after
+// `max_steps` steps or on reaching the goal
+impl Environment for MazeNavigator {
+    fn observation_space(&self) -> Space {
+        Space::uniform(10, 0., 1.)
+    }
+
+    fn action_space(&self) -> Space {
+        Space::uniform(2, -1., 1.)
+    }
+
+    fn reset(&mut self) -> Vec<f64> {
+        self.position = self.maze.start;
+        self.heading = self.maze.start_heading;
+        self.speed = 0.;
+        self.angular_velocity = 0.;
+        self.steps = 0;
+
+        self.observation()
+    }
+
+    fn step(&mut self, action: &[f64]) -> Step {
+        self.act(action);
+
+        let done = self.reached_goal() || self.steps >= self.max_steps;
+
+        Step {
+            observation: self.observation(),
+            reward: if done { self.fitness() } else { 0. },
+            done,
+        }
+    }
+}
+
+// Navigating a deceptive maze, where heading straight for the goal leads into
+// dead ends. Fitness is 1 minus the final distance to the goal relative to the
+// size of the maze, and the final position is the behavior descriptor.
+// `maze` is "medium", "hard", the path of a maze file or a table with the
+// start, start_heading, goal and walls of a maze.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MazeNavigation {
+    pub maze: Maze,
+    pub max_steps: u64,
+    pub goal_radius: f64,
+}
+
+impl Default for MazeNavigation {
+    fn default() -> Self {
+        Self {
+            maze: Maze::medium(),
+            max_steps: 400,
+            goal_radius: 5.,
+        }
+    }
+}
+
+impl MazeNavigation {
+    pub fn navigator(&self) -> MazeNavigator {
+        MazeNavigator {
+            max_steps: self.max_steps,
+            goal_radius: self.goal_radius,
+            ..MazeNavigator::new(self.maze.clone())
+        }
+    }
+
+    // The navigator at the end of the Genome's run
+    pub fn run(&self, genome: &Genome) -> Result<MazeNavigator, HyperDarwinError> {
+        let mut navigator = self.navigator();
+        EpisodeRunner::new().episode(&mut navigator, genome)?;
+
+        Ok(navigator)
+    }
+}
+
+impl Task for MazeNavigation {
+    fn name(&self) -> &'static str {
+        "maze"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Direct {
+            inputs: 10,
+            outputs: 2,
+        }
+    }
+
+    fn evaluate(&self, genome: &Genome, _hyper: &HyperSettings) -> f64 {
+        self.run(genome).map_or(0., |navigator| navigator.fitness())
+    }
+
+    fn behavior(&self, genome: &Genome, _hyper: &HyperSettings) -> Option<Vec<f64>> {
+        let navigator = self.run(genome).ok()?;
+
+        Some(vec![navigator.position.0, navigator.position.1])
+    }
+
+    fn evaluate_with_behavior(
+        &self,
+        genome: &Genome,
+        _hyper: &HyperSettings,
+    ) -> (f64, Option<Vec<f64>>) {
+        match self.run(genome) {
+            Ok(navigator) => (
+                navigator.fitness(),
+                Some(vec![navigator.position.0, navigator.position.1]),
+            ),
+            Err(_) => (0., None),
+        }
+    }
+
+    fn solved(&self, genome: &Genome, _hyper: &HyperSettings) -> bool {
+        self.run(genome)
+            .is_ok_and(|navigator| navigator.reached_goal())
+    }
+}
@@ -0,0 +1,99 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+mod boxes;
+mod checkers;
+mod maze;
+mod parity;
+mod pole_balancing;
+mod retina;
+mod sine;
+mod xor;
+
+pub use boxes::{Boxes, BoxesReport, BoxesTrial};
+pub use checkers::{
+    minimax, play, playable_squares, Board, Checkers, Evaluator, Game, Material, Move, Outcome,
+    Piece, Player, Side, Square,
+};
+pub use maze::{Maze, MazeNavigation, MazeNavigator, Wall};
+pub use parity::{Parity, MAX_PARITY_BITS};
+pub use pole_balancing::{
+    CartPole, PoleBalancing, Poles, GENERALIZATION_STEPS, GENERALIZATION_THRESHOLD, LONG_RUN_STEPS,
+};
+pub use retina::Retina;
+pub use sine::Sine;
+pub use xor::Xor;
+
+// Largest error on any case of a classification task for it to count as solved.
+// Just under 0.5 leaves outputs a hair's breadth from the wrong answer.
+pub(crate) const SOLVED_ERROR: f64 = 0.4;
+
+// A problem with a fitness function, used by the experiment runner
+pub trait Task: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Encoding the evolved Genomes must have
+    fn encoding(&self) -> Encoding;
+
+    // Fitness of a Genome, higher is better and never negative
+    fn evaluate(&self, genome: &Genome, hyper: &HyperSettings) -> f64;
+
+    // Behavior descriptor for the novelty fitness modes, None for tasks that
+    // don't define one
+    fn behavior(&self, _genome: &Genome, _hyper: &HyperSettings) -> Option<Vec<f64>> {
+        None
+    }
+
+    // Both at once, for tasks that can get them from a single run
+    fn evaluate_with_behavior(
+        &self,
+        genome: &Genome,
+        hyper: &HyperSettings,
+    ) -> (f64, Option<Vec<f64>>) {
+        (self.evaluate(genome, hyper), self.behavior(genome, hyper))
+    }
+
+    // Success criterion, checked on the best Genome of each generation
+    fn solved(&self, genome: &Genome, hyper: &HyperSettings) -> bool;
+}
+
+// Names of the built-in tasks
+pub const TASKS: &[&str] = &[
+    "boxes",
+    "checkers",
+    "maze",
+    "parity",
+    "pole_balancing",
+    "retina",
+    "sine",
+    "xor",
+];
+
+// Builds a built-in task, `params` holds its options with missing ones
+// taking their defaults
+pub fn build(name: &str, params: &Value) -> Result<Box<dyn Task>, HyperDarwinError> {
+    match name {
+        "boxes" => Ok(Box::new(parse::<Boxes>(params)?)),
+        "checkers" => Ok(Box::new(parse::<Checkers>(params)?)),
+        "maze" => Ok(Box::new(parse::<MazeNavigation>(params)?)),
+        "parity" => Ok(Box::new(parse::<Parity>(params)?)),
+        "pole_balancing" => Ok(Box::new(parse::<PoleBalancing>(params)?)),
+        "retina" => Ok(Box::new(parse::<Retina>(params)?)),
+        "sine" => Ok(Box::new(parse::<Sine>(params)?)),
+        "xor" => Ok(Box::new(parse::<Xor>(params)?)),
+        _ => Err(HyperDarwinError::UnknownTask(name.to_string())),
+    }
+}
+
+fn parse<T: DeserializeOwned>(params: &Value) -> Result<T, HyperDarwinError> {
+    let params = match params {
+        Value::Null => Value::Object(Default::default()),
+        params => params.clone(),
+    };
+
+    Ok(serde_json::from_value(params)?)
+}
@@ -0,0 +1,113 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+use crate::tasks::{Task, SOLVED_ERROR};
+
+use serde::{Deserialize, Serialize};
+
+use std::convert::TryFrom;
+
+// Every case is evaluated each time, 2^16 of them is already plenty
+pub const MAX_PARITY_BITS: u32 = 16;
+
+// N-bit odd parity with a single-output network: the output has to be on
+// when an odd number of inputs is on. Inputs are 0 or 1 and the output is
+// read in [0, 1]. Fitness is the classic NEAT ((n - error) / n)² over all 2^N
+// cases, and the task is solved once every case is within 0.4 of its target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ParityParams")]
+pub struct Parity {
+    pub(crate) bits: u32, // 1 to MAX_PARITY_BITS
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ParityParams {
+    bits: u32,
+}
+
+impl Default for ParityParams {
+    fn default() -> Self {
+        Self { bits: 3 }
+    }
+}
+
+impl TryFrom<ParityParams> for Parity {
+    type Error = HyperDarwinError;
+
+    fn try_from(params: ParityParams) -> Result<Self, Self::Error> {
+        Self::new(params.bits)
+    }
+}
+
+impl Default for Parity {
+    fn default() -> Self {
+        Self { bits: 3 }
+    }
+}
+
+impl Parity {
+    pub fn new(bits: u32) -> Result<Self, HyperDarwinError> {
+        if bits == 0 || bits > MAX_PARITY_BITS {
+            return Err(HyperDarwinError::InvalidParityBits(bits));
+        }
+
+        Ok(Self { bits })
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    // Every input combination with its expected output
+    pub fn cases(&self) -> Vec<(Vec<f64>, f64)> {
+        (0..1u64 << self.bits)
+            .map(|case| {
+                let inputs = (0..self.bits)
+                    .map(|bit| ((case >> bit) & 1) as f64)
+                    .collect();
+
+                (inputs, (case.count_ones() % 2) as f64)
+            })
+            .collect()
+    }
+
+    // Absolute error on each case, None if the Genome can't be fed
+    pub fn errors(&self, genome: &Genome) -> Option<Vec<f64>> {
+        self.cases()
+            .iter()
+            .map(|(inputs, target)| {
+                let out = genome.feed_forward(inputs).ok()?;
+                Some(((out + 1.) / 2. - target).abs())
+            })
+            .collect()
+    }
+}
+
+impl Task for Parity {
+    fn name(&self) -> &'static str {
+        "parity"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Direct {
+            inputs: self.bits,
+            outputs: 1,
+        }
+    }
+
+    fn evaluate(&self, genome: &Genome, _hyper: &HyperSettings) -> f64 {
+        match self.errors(genome) {
+            Some(errors) => {
+                let cases = errors.len() as f64;
+                ((cases - errors.iter().sum::<f64>()) / cases).powi(2)
+            }
+            None => 0.,
+        }
+    }
+
+    fn solved(&self, genome: &Genome, _hyper: &HyperSettings) -> bool {
+        self.errors(genome)
+            .is_some_and(|errors| errors.iter().all(|&err| err < SOLVED_ERROR))
+    }
+}
@@ -0,0 +1,398 @@
+use crate::environment::{Environment, EpisodeRunner, Space, Step};
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+use crate::tasks::Task;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+const GRAVITY: f64 = -9.8;
+const MASS_CART: f64 = 1.;
+const MASS_POLE_1: f64 = 0.1;
+const LENGTH_1: f64 = 0.5; // Half lengths
+const MASS_POLE_2: f64 = 0.01;
+const LENGTH_2: f64 = 0.05;
+const POLE_FRICTION: f64 = 0.000002;
+const FORCE_MAG: f64 = 10.;
+const TAU: f64 = 0.01; // Integration step, two per action
+
+const TRACK_LIMIT: f64 = 2.4;
+const TWELVE_DEGREES: f64 = 0.2094384;
+const THIRTY_SIX_DEGREES: f64 = 0.628329;
+
+// Steps of the long run every solution has to survive
+pub const LONG_RUN_STEPS: u64 = 100_000;
+
+// Steps of each start of the generalization test, and how many of its 625
+// starts have to be balanced to pass it
+pub const GENERALIZATION_STEPS: u64 = 1000;
+pub const GENERALIZATION_THRESHOLD: usize = 200;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Poles {
+    Single, // Fails beyond 12 degrees
+    Double, // Fails beyond 36 degrees
+}
+
+// A cart on a 4.8m track balancing one or two hinged poles, integrated with
+// fourth order Runge-Kutta as in Wieland's equations. Actions lie in [-1, 1]
+// and push the cart with up to 10N either way, each lasting 0.02s.
+//
+// Markovian observations are the cart position and velocity followed by the
+// angle and angular velocity of each pole. Non-Markovian ones leave out every
+// velocity, so balancing needs memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CartPole {
+    pub poles: Poles,
+    pub markovian: bool,
+    pub state: [f64; 6], // x, x', θ1, θ1', θ2, θ2' with θ2 unused for a single pole
+    pub steps: u64,
+}
+
+impl CartPole {
+    pub fn new(poles: Poles, markovian: bool) -> Self {
+        let mut cart = Self {
+            poles,
+            markovian,
+            state: [0.; 6],
+            steps: 0,
+        };
+
+        cart.reset();
+        cart
+    }
+
+    // Back to the standard start with the first pole leaning 4 degrees
+    pub fn reset(&mut self) {
+        self.reset_to([0., 0., 0.07, 0., 0., 0.]);
+    }
+
+    pub fn reset_to(&mut self, state: [f64; 6]) {
+        self.state = state;
+        self.steps = 0;
+    }
+
+    pub fn observation_size(&self) -> usize {
+        match (self.poles, self.markovian) {
+            (Poles::Single, true) => 4,
+            (Poles::Single, false) => 2,
+            (Poles::Double, true) => 6,
+            (Poles::Double, false) => 3,
+        }
+    }
+
+    // The state scaled to roughly [-1, 1]
+    pub fn observation(&self) -> Vec<f64> {
+        let s = &self.state;
+
+        let mut obs = if self.markovian {
+            vec![s[0] / 4.8, s[1] / 2., s[2] / 0.52, s[3] / 2.]
+        } else {
+            vec![s[0] / 4.8, s[2] / 0.52]
+        };
+
+        if self.poles == Poles::Double {
+            if self.markovian {
+                obs.extend_from_slice(&[s[4] / 0.52, s[5] / 2.]);
+            } else {
+                obs.push(s[4] / 0.52);
+            }
+        }
+
+        obs
+    }
+
+    pub fn failed(&self) -> bool {
+        let s = &self.state;
+
+        match self.poles {
+            Poles::Single => s[0].abs() > TRACK_LIMIT || s[2].abs() > TWELVE_DEGREES,
+            Poles::Double => {
+                s[0].abs() > TRACK_LIMIT
+                    || s[2].abs() > THIRTY_SIX_DEGREES
+                    || s[4].abs() > THIRTY_SIX_DEGREES
+            }
+        }
+    }
+
+    // Applies an action, returning whether the poles are still balanced
+    pub fn step(&mut self, action: f64) -> bool {
+        let force = action.clamp(-1., 1.) * FORCE_MAG;
+
+        for _ in 0..2 {
+            self.runge_kutta(force);
+        }
+
+        self.steps += 1;
+
+        !self.failed()
+    }
+
+    fn derivatives(&self, s: &[f64; 6], force: f64) -> [f64; 6] {
+        let pole = |mass: f64, length: f64, theta: f64, theta_dot: f64| {
+            let (sin, cos) = theta.sin_cos();
+            let ml = mass * length;
+            let friction = POLE_FRICTION * theta_dot / ml;
+
+            let effective_force =
+                ml * theta_dot * theta_dot * sin + 0.75 * mass * cos * (friction + GRAVITY * sin);
+            let effective_mass = mass * (1. - 0.75 * cos * cos);
+
+            (
+                effective_force,
+                effective_mass,
+                cos,
+                GRAVITY * sin + friction,
+            )
+        };
+
+        let (f1, m1, cos1, rest1) = pole(MASS_POLE_1, LENGTH_1, s[2], s[3]);
+
+        let (f2, m2, cos2, rest2) = match self.poles {
+            Poles::Single => (0., 0., 0., 0.),
+            Poles::Double => pole(MASS_POLE_2, LENGTH_2, s[4], s[5]),
+        };
+
+        let x_acc = (force + f1 + f2) / (m1 + m2 + MASS_CART);
+
+        let theta_acc_2 = match self.poles {
+            Poles::Single => 0.,
+            Poles::Double => -0.75 * (x_acc * cos2 + rest2) / LENGTH_2,
+        };
+
+        [
+            s[1],
+            x_acc,
+            s[3],
+            -0.75 * (x_acc * cos1 + rest1) / LENGTH_1,
+            s[5],
+            theta_acc_2,
+        ]
+    }
+
+    fn runge_kutta(&mut self, force: f64) {
+        let offset = |s: &[f64; 6], d: &[f64; 6], h: f64| {
+            let mut next = *s;
+            next.iter_mut()
+                .zip(d.iter())
+                .for_each(|(v, dv)| *v += h * dv);
+            next
+        };
+
+        let s = self.state;
+        let k1 = self.derivatives(&s, force);
+        let k2 = self.derivatives(&offset(&s, &k1, TAU / 2.), force);
+        let k3 = self.derivatives(&offset(&s, &k2, TAU / 2.), force);
+        let k4 = self.derivatives(&offset(&s, &k3, TAU), force);
+
+        for i in 0..6 {
+            self.state[i] += TAU / 6. * (k1[i] + 2. * k2[i] + 2. * k3[i] + k4[i]);
+        }
+    }
+
+    // Runs a controller from the current state until the poles fall or
+    // `max_steps` actions were taken, returning the number of actions
+    pub fn balance<F>(&mut self, mut controller: F, max_steps: u64) -> u64
+    where
+        F: FnMut(&[f64]) -> f64,
+    {
+        let start = self.steps;
+
+        while self.steps - start < max_steps {
+            let action = controller(&self.observation());
+
+            if !self.step(action) {
+                break;
+            }
+        }
+
+        self.steps - start
+    }
+
+    // Gruau's fitness for the non-Markovian task over 1000 steps, which
+    // rewards controllers that keep the cart and first pole from oscillating
+    pub fn damping_fitness<F>(&mut self, mut controller: F) -> f64
+    where
+        F: FnMut(&[f64]) -> f64,
+    {
+        let start = self.steps;
+        let mut jiggle = Vec::with_capacity(GENERALIZATION_STEPS as usize);
+
+        while self.steps - start < GENERALIZATION_STEPS {
+            let action = controller(&self.observation());
+            let balanced = self.step(action);
+
+            let s = &self.state;
+            jiggle.push(s[0].abs() + s[1].abs() + s[2].abs() + s[3].abs());
+
+            if !balanced {
+                break;
+            }
+        }
+
+        let steps = self.steps - start;
+        let f1 = steps as f64 / GENERALIZATION_STEPS as f64;
+
+        let f2 = if steps < 100 {
+            0.
+        } else {
+            0.75 / jiggle.iter().rev().take(100).sum::<f64>()
+        };
+
+        0.1 * f1 + 0.9 * f2
+    }
+
+    // Whether a controller balances the poles for 100,000 steps from the
+    // standard start
+    pub fn long_run_test<F>(&mut self, controller: F) -> bool
+    where
+        F: FnMut(&[f64]) -> f64,
+    {
+        self.reset();
+        self.balance(controller, LONG_RUN_STEPS) == LONG_RUN_STEPS
+    }
+
+    // The 625 starts of the generalization test, spreading the cart position
+    // and velocity and the first pole's angle and angular velocity over 5
+    // values each
+    pub fn generalization_starts() -> Vec<[f64; 6]> {
+        let values = [0.05, 0.25, 0.5, 0.75, 0.95];
+        let mut starts = Vec::with_capacity(625);
+
+        for &x in &values {
+            for &x_dot in &values {
+                for &theta in &values {
+                    for &theta_dot in &values {
+                        starts.push([
+                            x * 4.32 - 2.16,
+                            x_dot * 2.70 - 1.35,
+                            theta * 0.12566304 - 0.06283152,
+                            theta_dot * 0.30019504 - 0.15009752,
+                            0.,
+                            0.,
+                        ]);
+                    }
+                }
+            }
+        }
+
+        starts
+    }
+
+    // Number of the 625 starts from which a controller balances the poles for
+    // 1000 steps. `new_controller` is called once per start so stateful
+    // controllers begin afresh.
+    pub fn generalization_test<F, C>(&mut self, mut new_controller: F) -> usize
+    where
+        F: FnMut() -> C,
+        C: FnMut(&[f64]) -> f64,
+    {
+        Self::generalization_starts()
+            .into_iter()
+            .filter(|&start| {
+                self.reset_to(start);
+                self.balance(new_controller(), GENERALIZATION_STEPS) == GENERALIZATION_STEPS
+            })
+            .count()
+    }
+}
+
+// Each balanced step earns a reward of 1 and the episode ends when the poles
+// fall. Episodes have no length limit of their own.
+impl Environment for CartPole {
+    fn observation_space(&self) -> Space {
+        Space::uniform(self.observation_size(), -1., 1.)
+    }
+
+    fn action_space(&self) -> Space {
+        Space::uniform(1, -1., 1.)
+    }
+
+    fn reset(&mut self) -> Vec<f64> {
+        CartPole::reset(self);
+        self.observation()
+    }
+
+    fn step(&mut self, action: &[f64]) -> Step {
+        let balanced = CartPole::step(self, action[0]);
+
+        Step {
+            observation: self.observation(),
+            reward: if balanced { 1. } else { 0. },
+            done: !balanced,
+        }
+    }
+}
+
+// Pole balancing with a Genome pushing the cart. The Markovian task scores
+// the fraction of `max_steps` balanced and is solved by balancing all of them.
+// The non-Markovian task uses the damping fitness and is solved by passing the
+// 100,000 step test and balancing at least 200 of the 625 generalization starts.
+// Without velocities it needs recurrent Genomes, see `recurrent_conn_rate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoleBalancing {
+    pub poles: Poles,
+    pub markovian: bool,
+    pub max_steps: u64,
+}
+
+impl Default for PoleBalancing {
+    fn default() -> Self {
+        Self {
+            poles: Poles::Double,
+            markovian: true,
+            max_steps: LONG_RUN_STEPS,
+        }
+    }
+}
+
+impl PoleBalancing {
+    pub fn cart(&self) -> CartPole {
+        CartPole::new(self.poles, self.markovian)
+    }
+}
+
+// Remembers across steps, recurrent Genomes need that for the non-Markovian task
+fn controller(genome: &Genome) -> impl FnMut(&[f64]) -> f64 + '_ {
+    let mut memory = HashMap::new();
+    move |obs: &[f64]| genome.activate(obs, &mut memory).map_or(0., |out| out[0])
+}
+
+impl Task for PoleBalancing {
+    fn name(&self) -> &'static str {
+        "pole_balancing"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Direct {
+            inputs: self.cart().observation_size() as u32,
+            outputs: 1,
+        }
+    }
+
+    fn evaluate(&self, genome: &Genome, _hyper: &HyperSettings) -> f64 {
+        let mut cart = self.cart();
+
+        if self.markovian {
+            EpisodeRunner::new()
+                .max_steps(self.max_steps)
+                .run(&mut cart, genome)
+                .map_or(0., |balanced| balanced / self.max_steps.max(1) as f64)
+        } else {
+            cart.damping_fitness(controller(genome))
+        }
+    }
+
+    fn solved(&self, genome: &Genome, _hyper: &HyperSettings) -> bool {
+        let mut cart = self.cart();
+
+        if self.markovian {
+            return cart.balance(controller(genome), self.max_steps) == self.max_steps;
+        }
+
+        cart.long_run_test(controller(genome))
+            && cart.generalization_test(|| controller(genome)) >= GENERALIZATION_THRESHOLD
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::HyperDarwinError;
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+use crate::substrate::Network;
+use crate::tasks::{Task, SOLVED_ERROR};
+
+use serde::{Deserialize, Serialize};
+
+// The objects of Kashtan & Alon (2005), 8 of the 16 patterns of each patch.
+// Patterns are 2×2 patches as bits (top left, top right, bottom left, bottom
+// right) from the highest down.
+const LEFT_OBJECTS: [u8; 8] = [
+    0b0001, 0b0010, 0b0011, 0b0101, 0b0111, 0b1010, 0b1011, 0b1111,
+];
+const RIGHT_OBJECTS: [u8; 8] = [
+    0b0100, 0b0101, 0b1000, 0b1010, 0b1100, 0b1101, 0b1110, 0b1111,
+];
+
+// The Left & Right Retina modularity problem: an 8 pixel retina made of two
+// 2×2 patches, and the output has to be on only when both the left and the
+// right patch show an object, from their own sets of 8 objects. The retina sits on the bottom row of a layered
+// substrate with the patches side by side, so the problem splits along the
+// geometry of the network. Pixels are fed as 1 when on and -1 when off.
+//
+// Fitness is ((n - error) / n)² over all 256 patterns, and the task is solved
+// once every pattern is within 0.4 of its target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Retina {
+    pub hidden: Vec<usize>, // Sizes of the hidden layers, bottom to top
+}
+
+impl Default for Retina {
+    fn default() -> Self {
+        Self { hidden: vec![2] }
+    }
+}
+
+impl Retina {
+    pub fn is_left_object(pattern: u8) -> bool {
+        LEFT_OBJECTS.contains(&pattern)
+    }
+
+    pub fn is_right_object(pattern: u8) -> bool {
+        RIGHT_OBJECTS.contains(&pattern)
+    }
+
+    // Every pattern of the left and right patches with its expected output.
+    // Pixels are ordered left patch then right patch, each row by row.
+    pub fn cases(&self) -> Vec<(Vec<f64>, f64)> {
+        (0..=255u8)
+            .map(|pattern| {
+                let (left, right) = (pattern >> 4, pattern & 0b1111);
+
+                let inputs = (0..8)
+                    .rev()
+                    .map(|bit| if (pattern >> bit) & 1 == 1 { 1. } else { -1. })
+                    .collect();
+
+                let target = Self::is_left_object(left) && Self::is_right_object(right);
+
+                (inputs, if target { 1. } else { 0. })
+            })
+            .collect()
+    }
+
+    // Layers from the retina at y = -1 up to the single output at y = 1
+    pub fn layers(&self) -> Vec<Vec<(f64, f64)>> {
+        let depth = self.hidden.len() + 1;
+
+        let retina = vec![
+            (-1., -0.8),
+            (-0.6, -0.8),
+            (-1., -1.),
+            (-0.6, -1.),
+            (0.6, -0.8),
+            (1., -0.8),
+            (0.6, -1.),
+            (1., -1.),
+        ];
+
+        let hidden = self
+            .hidden
+            .iter()
+            .enumerate()
+            .map(|(i, &len)| Network::row(len, -1. + 2. * (i + 1) as f64 / depth as f64));
+
+        let mut layers = vec![retina];
+        layers.extend(hidden);
+        layers.push(Network::row(1, 1.));
+        layers
+    }
+
+    // Decodes the network, with the distance from the center as third
+    // parameter when the CPPN takes one
+    pub fn network(
+        &self,
+        genome: &Genome,
+        hyper: &HyperSettings,
+    ) -> Result<Network, HyperDarwinError> {
+        if genome.inputs() == 6 {
+            let dist = |x: f64, y: f64| (x * x + y * y).sqrt();
+            Network::decode(genome, self.layers(), hyper, Some(dist))
+        } else {
+            Network::decode(genome, self.layers(), hyper, None::<fn(f64, f64) -> f64>)
+        }
+    }
+
+    // Absolute error on each pattern
+    pub fn errors(
+        &self,
+        genome: &Genome,
+        hyper: &HyperSettings,
+    ) -> Result<Vec<f64>, HyperDarwinError> {
+        let network = self.network(genome, hyper)?;
+
+        self.cases()
+            .iter()
+            .map(|(inputs, target)| Ok((network.activate(inputs)?[0] - target).abs()))
+            .collect()
+    }
+}
+
+impl Task for Retina {
+    fn name(&self) -> &'static str {
+        "retina"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Cppn
+    }
+
+    fn evaluate(&self, genome: &Genome, hyper: &HyperSettings) -> f64 {
+        match self.errors(genome, hyper) {
+            Ok(errors) => {
+                let cases = errors.len() as f64;
+                ((cases - errors.iter().sum::<f64>()) / cases).powi(2)
+            }
+            Err(_) => 0.,
+        }
+    }
+
+    fn solved(&self, genome: &Genome, hyper: &HyperSettings) -> bool {
+        self.errors(genome, hyper)
+            .is_ok_and(|errors| errors.iter().all(|&err| err < SOLVED_ERROR))
+    }
+}
@@ -0,0 +1,57 @@
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+use crate::tasks::Task;
+
+use serde::{Deserialize, Serialize};
+
+use std::f64::consts::PI;
+
+// Regression of sin(πx) over [-1, 1] with a single-input, single-output network.
+// Fitness is 1 minus half the mean absolute error, so it lies within [0, 1].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sine {
+    pub samples: usize,
+    pub target_fitness: f64,
+}
+
+impl Default for Sine {
+    fn default() -> Self {
+        Self {
+            samples: 21,
+            target_fitness: 0.95,
+        }
+    }
+}
+
+impl Task for Sine {
+    fn name(&self) -> &'static str {
+        "sine"
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Direct {
+            inputs: 1,
+            outputs: 1,
+        }
+    }
+
+    fn evaluate(&self, genome: &Genome, _hyper: &HyperSettings) -> f64 {
+        let samples = self.samples.max(2);
+
+        let error = (0..samples)
+            .map(|i| -1. + 2. * i as f64 / (samples - 1) as f64)
+            .map(|x| match genome.feed_forward(&[x]) {
+                Ok(out) => (out - (PI * x).sin()).abs(),
+                Err(_) => 2.,
+            })
+            .sum::<f64>()
+            / samples as f64;
+
+        (1. - error / 2.).max(0.)
+    }
+
+    fn solved(&self, genome: &Genome, hyper: &HyperSettings) -> bool {
+        self.evaluate(genome, hyper) >= self.target_fitness
+    }
+}
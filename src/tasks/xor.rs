@@ -0,0 +1,34 @@
+use crate::genome::Genome;
+use crate::settings::{Encoding, HyperSettings};
+use crate::tasks::{Parity, Task};
+
+use serde::{Deserialize, Serialize};
+
+// Exclusive or of two inputs, the 2-bit case of Parity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Xor {}
+
+impl Xor {
+    fn parity(&self) -> Parity {
+        Parity { bits: 2 }
+    }
+}
+
+impl Task for Xor {
+    fn name(&self) -> &'static str {
+        "xor"
+    }
+
+    fn encoding(&self) -> Encoding {
+        self.parity().encoding()
+    }
+
+    fn evaluate(&self, genome: &Genome, hyper: &HyperSettings) -> f64 {
+        self.parity().evaluate(genome, hyper)
+    }
+
+    fn solved(&self, genome: &Genome, hyper: &HyperSettings) -> bool {
+        self.parity().solved(genome, hyper)
+    }
+}
@@ -0,0 +1,91 @@
+use hyper_darwin::{Archipelago, Settings, Topology};
+
+// Three islands of 8 where island i's k-th Genome has fitness 100 i + k, so
+// every island's best are worse than those of the next one
+fn archipelago(topology: Topology, migrants: usize) -> Archipelago {
+    let settings = vec![Settings::new(8).direct(2, 1); 3];
+    let mut archipelago = Archipelago::new(settings)
+        .unwrap()
+        .topology(topology)
+        .migrants(migrants);
+
+    evaluate(&mut archipelago);
+    archipelago
+}
+
+fn evaluate(archipelago: &mut Archipelago) {
+    for (i, island) in archipelago.islands_mut().iter_mut().enumerate() {
+        for (k, genome) in island.get_citizens().iter_mut().enumerate() {
+            genome.fitness = (100 * i + k) as f64;
+        }
+    }
+}
+
+// Ids of an island's Genomes, fittest first
+fn ids(archipelago: &Archipelago, island: usize) -> Vec<u64> {
+    let mut genomes = archipelago.islands()[island].citizens().to_vec();
+    genomes.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    genomes.iter().map(|g| g.id).collect()
+}
+
+#[test]
+fn ring_migration_sends_the_best_to_the_next_island() {
+    let mut archipelago = archipelago(Topology::Ring, 2);
+    let before = (0..3).map(|i| ids(&archipelago, i)).collect::<Vec<_>>();
+
+    archipelago.migrate();
+
+    for dest in 0..3 {
+        let src = (dest + 2) % 3;
+        let after = ids(&archipelago, dest);
+
+        assert_eq!(after.len(), 8);
+
+        // The newcomers replaced the two worst locals
+        let mut expected = before[dest][..6].to_vec();
+        expected.extend_from_slice(&before[src][..2]);
+        expected.sort_unstable();
+
+        let mut after = after;
+        after.sort_unstable();
+
+        assert_eq!(after, expected);
+    }
+}
+
+#[test]
+fn migrants_never_take_more_than_half_an_island() {
+    // Each island is sent 2 × 6 migrants but only takes the 4 fittest
+    let mut archipelago = archipelago(Topology::FullyConnected, 6);
+    let before = (0..3).map(|i| ids(&archipelago, i)).collect::<Vec<_>>();
+
+    archipelago.migrate();
+
+    // Island 0 gets four of island 2's, island 2 four of island 1's
+    for &(dest, src) in &[(0, 2), (1, 2), (2, 1)] {
+        let after = ids(&archipelago, dest);
+
+        assert_eq!(after.len(), 8);
+
+        let migrants = after
+            .iter()
+            .filter(|id| !before[dest].contains(id))
+            .copied()
+            .collect::<Vec<u64>>();
+
+        assert_eq!(migrants, before[src][..4].to_vec());
+        assert!(before[dest][..4].iter().all(|id| after.contains(id)));
+    }
+}
+
+#[test]
+fn a_zero_interval_never_migrates() {
+    let mut archipelago = archipelago(Topology::Ring, 2).migration_interval(0);
+
+    for _ in 0..3 {
+        evaluate(&mut archipelago);
+        archipelago.next_generation();
+    }
+
+    assert_eq!(archipelago.generations, 3);
+}
@@ -0,0 +1,188 @@
+use hyper_darwin::tasks::{Board, Move, Piece, Side, Square};
+
+fn perft(board: &Board, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    board
+        .moves()
+        .iter()
+        .map(|mv| perft(&board.play(mv), depth - 1))
+        .sum()
+}
+
+fn man(side: Side) -> Option<Piece> {
+    Some(Piece { side, king: false })
+}
+
+fn king(side: Side) -> Option<Piece> {
+    Some(Piece { side, king: true })
+}
+
+fn board(to_move: Side, pieces: &[(Square, Option<Piece>)]) -> Board {
+    let mut board = Board::empty(to_move);
+
+    for &(sq, piece) in pieces {
+        board.set(sq, piece);
+    }
+
+    board
+}
+
+fn paths(board: &Board) -> Vec<Vec<Square>> {
+    let mut paths = board
+        .moves()
+        .into_iter()
+        .map(|mv| mv.path)
+        .collect::<Vec<_>>();
+
+    paths.sort();
+    paths
+}
+
+#[test]
+fn perft_from_the_start() {
+    // Published perft counts for English draughts
+    let expected = [7, 49, 302, 1469, 7361, 36768, 179740];
+
+    for (depth, &count) in expected.iter().enumerate() {
+        assert_eq!(
+            perft(&Board::new(), depth + 1),
+            count,
+            "depth {}",
+            depth + 1
+        );
+    }
+}
+
+#[test]
+fn captures_are_compulsory() {
+    let board = board(
+        Side::Black,
+        &[
+            ((2, 2), man(Side::Black)),
+            ((0, 6), man(Side::Black)),
+            ((3, 3), man(Side::White)),
+        ],
+    );
+
+    assert_eq!(
+        board.moves(),
+        vec![Move {
+            path: vec![(2, 2), (4, 4)],
+            captures: vec![(3, 3)],
+        }]
+    );
+}
+
+#[test]
+fn men_only_move_forwards() {
+    let black = board(Side::Black, &[((2, 2), man(Side::Black))]);
+    assert_eq!(
+        paths(&black),
+        vec![vec![(2, 2), (3, 1)], vec![(2, 2), (3, 3)]]
+    );
+
+    let white = board(Side::White, &[((2, 2), man(Side::White))]);
+    assert_eq!(
+        paths(&white),
+        vec![vec![(2, 2), (1, 1)], vec![(2, 2), (1, 3)]]
+    );
+
+    let kings = board(Side::Black, &[((2, 2), king(Side::Black))]);
+    assert_eq!(paths(&kings).len(), 4);
+}
+
+#[test]
+fn jumps_carry_on_to_the_end() {
+    // The second jump can go either way, and both must be finished
+    let board = board(
+        Side::Black,
+        &[
+            ((0, 0), man(Side::Black)),
+            ((1, 1), man(Side::White)),
+            ((3, 1), man(Side::White)),
+            ((3, 3), man(Side::White)),
+            ((5, 5), man(Side::White)),
+        ],
+    );
+
+    assert_eq!(
+        paths(&board),
+        vec![
+            vec![(0, 0), (2, 2), (4, 0)],
+            vec![(0, 0), (2, 2), (4, 4), (6, 6)],
+        ]
+    );
+
+    let triple = board
+        .moves()
+        .into_iter()
+        .find(|mv| mv.path.len() == 4)
+        .unwrap();
+    let after = board.play(&triple);
+
+    assert_eq!(triple.captures, vec![(1, 1), (3, 3), (5, 5)]);
+    assert_eq!(after.get((6, 6)), man(Side::Black));
+    assert_eq!(after.pieces(Side::White).count(), 1);
+    assert_eq!(after.to_move, Side::White);
+}
+
+#[test]
+fn crowning_ends_a_capture() {
+    // Landing on the back row crowns the man, which may not jump back as a king
+    let board = board(
+        Side::Black,
+        &[
+            ((5, 1), man(Side::Black)),
+            ((6, 2), man(Side::White)),
+            ((6, 4), man(Side::White)),
+        ],
+    );
+
+    let moves = board.moves();
+
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].path, vec![(5, 1), (7, 3)]);
+    assert_eq!(board.play(&moves[0]).get((7, 3)), king(Side::Black));
+}
+
+#[test]
+fn kings_can_capture_round_to_their_square() {
+    let board = board(
+        Side::Black,
+        &[
+            ((2, 2), king(Side::Black)),
+            ((3, 1), man(Side::White)),
+            ((3, 3), man(Side::White)),
+            ((5, 1), man(Side::White)),
+            ((5, 3), man(Side::White)),
+        ],
+    );
+
+    let moves = board.moves();
+
+    assert_eq!(moves.len(), 2);
+    assert!(moves.iter().all(|mv| mv.captures.len() == 4));
+    assert!(moves.iter().all(|mv| mv.path.last() == Some(&(2, 2))));
+
+    let after = board.play(&moves[0]);
+
+    assert_eq!(after.get((2, 2)), king(Side::Black));
+    assert!(after.moves().is_empty());
+}
+
+#[test]
+fn own_pieces_are_not_captured() {
+    let board = board(
+        Side::Black,
+        &[((0, 0), man(Side::Black)), ((1, 1), man(Side::Black))],
+    );
+
+    assert!(board.moves().iter().all(|mv| mv.captures.is_empty()));
+    assert_eq!(
+        paths(&board),
+        vec![vec![(1, 1), (2, 0)], vec![(1, 1), (2, 2)]]
+    );
+}
@@ -0,0 +1,88 @@
+use hyper_darwin::{Experiment, ExperimentConfig, FitnessMode, Population, Settings};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("hyper_darwin_{}_{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+#[test]
+fn resumes_a_run_that_retired_species() {
+    let dir = output_dir("retired_species");
+
+    let config = ExperimentConfig {
+        task: String::from("xor"),
+        seed: Some(1),
+        generations: 30,
+        output_dir: dir.clone(),
+        checkpoint_interval: 0,
+        stop_when_solved: false,
+        settings: Settings::new(50)
+            .speciation_threshold(0.5)
+            .allowed_stagnancy(2),
+        ..ExperimentConfig::default()
+    };
+
+    let mut experiment = Experiment::new(config).unwrap();
+    experiment.run(|_| {}).unwrap();
+
+    assert!(!experiment.population().extinct_species().is_empty());
+
+    let mut resumed = Experiment::resume(dir.join("checkpoint.json"), Some(35)).unwrap();
+
+    assert_eq!(resumed.population().generations, 30);
+    assert_eq!(
+        resumed.population().extinct_species().len(),
+        experiment.population().extinct_species().len()
+    );
+
+    resumed.run(|_| {}).unwrap();
+    assert_eq!(resumed.population().generations, 35);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn infinite_crowding_distances_survive_serialization() {
+    hyper_darwin::seed(2);
+
+    let sets = Settings::new(20)
+        .direct(2, 1)
+        .fitness_mode(FitnessMode::MultiObjective);
+
+    let mut pop = Population::new(sets);
+
+    for (i, genome) in pop.get_citizens().iter_mut().enumerate() {
+        genome.set_objectives(vec![i as f64, (20 - i) as f64]);
+        genome.add_fitness(1.);
+    }
+
+    pop.next_generation();
+
+    let front = pop.pareto_front();
+    assert!(front.iter().any(|g| g.crowding_distance.is_infinite()));
+
+    let json = serde_json::to_string(&pop).unwrap();
+    let restored: Population = serde_json::from_str(&json).unwrap();
+
+    let distances = |pop: &Population| {
+        pop.pareto_front()
+            .iter()
+            .map(|g| g.crowding_distance)
+            .collect::<Vec<f64>>()
+    };
+
+    assert_eq!(distances(&restored), distances(&pop));
+}
+
+#[test]
+fn nan_fitness_is_refused() {
+    let mut genome = hyper_darwin::Genome::new(2, 1, false);
+    genome.fitness = f64::NAN;
+
+    assert!(serde_json::to_string(&genome).is_err());
+}
@@ -0,0 +1,125 @@
+use hyper_darwin::{Coevolution, Genome, HyperDarwinError, OpponentSampling, Settings};
+
+use std::collections::{HashMap, HashSet};
+
+fn coevolution(sampling: OpponentSampling) -> Coevolution {
+    let sets = Settings::new(10).direct(2, 1);
+
+    Coevolution::new(sets.clone(), sets).sampling(sampling)
+}
+
+// The opponents from Population B every Genome of A played. A's games come
+// first, and both Populations play as many, so they are the first half.
+fn games(coevo: &mut Coevolution) -> HashMap<u64, Vec<u64>> {
+    let mut calls = Vec::<(u64, u64)>::new();
+
+    coevo
+        .evaluate(|a, b| {
+            calls.push((a.id, b.id));
+            (b.id as f64, a.id as f64)
+        })
+        .unwrap();
+
+    let mut games = HashMap::<u64, Vec<u64>>::new();
+
+    for &(a, b) in &calls[..calls.len() / 2] {
+        games.entry(a).or_default().push(b);
+    }
+
+    games
+}
+
+fn ids(genomes: &[Genome]) -> HashSet<u64> {
+    genomes.iter().map(|g| g.id).collect()
+}
+
+#[test]
+fn random_sampling_plays_that_many_opponents() {
+    hyper_darwin::seed(21);
+
+    for &(amt, expected) in &[(3, 3), (10, 10), (25, 10)] {
+        let mut coevo = coevolution(OpponentSampling::Random(amt));
+        let pop_b = ids(coevo.population_b().citizens());
+
+        let games = games(&mut coevo);
+
+        assert_eq!(games.len(), 10);
+
+        for opponents in games.values() {
+            let distinct = opponents.iter().collect::<HashSet<_>>();
+
+            assert_eq!(opponents.len(), expected);
+            assert_eq!(distinct.len(), expected);
+            assert!(opponents.iter().all(|id| pop_b.contains(id)));
+        }
+    }
+}
+
+#[test]
+fn best_of_last_generation_plays_the_champion() {
+    hyper_darwin::seed(22);
+
+    let mut coevo = coevolution(OpponentSampling::BestOfLastGeneration);
+
+    // Without a champion yet, a single random opponent
+    let first = games(&mut coevo);
+    assert!(first.values().all(|opponents| opponents.len() == 1));
+
+    // B's fitness is the mean id of its opponents, its champion is the Genome
+    // that met the highest ids
+    let champ = coevo
+        .population_b()
+        .citizens()
+        .iter()
+        .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+        .unwrap()
+        .id;
+
+    coevo.next_generation();
+
+    let second = games(&mut coevo);
+
+    assert_eq!(second.len(), 10);
+    assert!(second.values().all(|opponents| opponents == &vec![champ]));
+}
+
+#[test]
+fn hall_of_fame_sampling_plays_past_champions() {
+    hyper_darwin::seed(23);
+
+    let mut coevo = coevolution(OpponentSampling::HallOfFame(2)).hall_of_fame_size(5);
+
+    // An empty hall of fame falls back to the current Population
+    let pop_b = ids(coevo.population_b().citizens());
+    let first = games(&mut coevo);
+    assert!(first.values().flatten().all(|id| pop_b.contains(id)));
+
+    for _ in 0..3 {
+        coevo.next_generation();
+        games(&mut coevo);
+    }
+
+    let hall = ids(coevo.hall_of_fame_b());
+    assert_eq!(coevo.hall_of_fame_b().len(), 3);
+
+    for opponents in games(&mut coevo).values() {
+        assert_eq!(opponents.len(), 2);
+        assert!(opponents.iter().all(|id| hall.contains(id)));
+    }
+}
+
+#[test]
+fn zero_opponents_are_refused() {
+    for &sampling in &[OpponentSampling::Random(0), OpponentSampling::HallOfFame(0)] {
+        let mut coevo = coevolution(sampling);
+
+        let mut played = false;
+        let result = coevo.evaluate(|_, _| {
+            played = true;
+            (1., 1.)
+        });
+
+        assert!(matches!(result, Err(HyperDarwinError::NoOpponents)));
+        assert!(!played);
+    }
+}
@@ -0,0 +1,150 @@
+use hyper_darwin::{
+    Activations, CompatibilityDistance, Connection, GeneComparison, Genome, NeatDistance, Node,
+    NodeGeneDistance, ParameterDistance, Settings,
+};
+
+// Inputs 1 and 2, bias 3 and output 4, plus the given hidden nodes. Every
+// node is Linear except those listed in `gaussian`.
+fn genome(hidden: &[u32], gaussian: &[u32], conns: &[(u32, u32, u32, f64)]) -> Genome {
+    let mut genome = Genome::new(2, 1, false);
+
+    genome.nodes.extend(
+        hidden
+            .iter()
+            .map(|&innov| Node::new(innov, 0.5, 0.5, Activations::Linear)),
+    );
+
+    for node in genome.nodes.iter_mut() {
+        node.activation = if gaussian.contains(&node.innov) {
+            Activations::Gaussian
+        } else {
+            Activations::Linear
+        };
+    }
+
+    genome.conns = conns
+        .iter()
+        .map(|&(innov, from, to, weight)| Connection::new(innov, from, to, weight, true))
+        .collect();
+
+    genome
+}
+
+// Matching 1, 2 and 3 with weights 0.5 apart on average, disjoint 4, 5 and 6
+// and excess 8. Node 4 differs in activation, hidden nodes 5 and 6 are each
+// in only one of them, and the bias weights into node 4 differ by 0.6.
+fn pair() -> (Genome, Genome) {
+    let a = genome(
+        &[5],
+        &[],
+        &[
+            (1, 1, 4, 0.5),
+            (2, 2, 4, 1.),
+            (3, 3, 4, 0.2),
+            (5, 1, 5, 1.),
+            (8, 5, 4, 1.),
+        ],
+    );
+
+    let b = genome(
+        &[6],
+        &[4],
+        &[
+            (1, 1, 4, 0.),
+            (2, 2, 4, 2.),
+            (3, 3, 4, 0.8),
+            (4, 3, 6, 1.),
+            (6, 6, 4, 1.),
+        ],
+    );
+
+    (a, b)
+}
+
+fn sets() -> Settings {
+    Settings::new(1)
+        .direct(2, 1)
+        .disjoint_coeff(1.)
+        .excess_coeff(2.)
+        .weight_coeff(0.5)
+        .activation_coeff(3.)
+        .node_coeff(4.)
+        .bias_coeff(5.)
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn genes_line_up_by_innovation() {
+    let (a, b) = pair();
+
+    for genes in &[
+        GeneComparison::new(&a.conns, &b.conns),
+        GeneComparison::new(&b.conns, &a.conns),
+    ] {
+        assert_eq!(genes.matching, 3.);
+        assert_eq!(genes.disjoint, 3.);
+        assert_eq!(genes.excess, 1.);
+        assert_close(genes.weight_difference, 2.1);
+        assert_close(genes.mean_weight_difference(), 0.7);
+    }
+}
+
+#[test]
+fn each_metric_weighs_its_terms() {
+    let (a, b) = pair();
+    let sets = sets();
+
+    // Below 20 genes nothing is normalized:
+    // 1 × 3 disjoint + 2 × 1 excess + 0.5 × 0.7 + 3 × 1 activation
+    assert_close(NeatDistance.distance(&a, &b, &sets), 8.35);
+
+    // Over 5 connections and 5 nodes, with the 2 unshared nodes:
+    // (1 × 3 + 2 × 1) / 5 + 0.5 × 0.7 + (4 × 2 + 3 × 1) / 5
+    assert_close(NodeGeneDistance.distance(&a, &b, &sets), 3.55);
+
+    // Plus 5 × 0.6 for the one bias weight they share
+    assert_close(ParameterDistance.distance(&a, &b, &sets), 6.55);
+}
+
+#[test]
+fn genomes_without_connections_are_compared_on_their_nodes() {
+    let sets = sets();
+    let empty = genome(&[], &[], &[]);
+    let metrics: [&dyn CompatibilityDistance; 3] =
+        [&NeatDistance, &NodeGeneDistance, &ParameterDistance];
+
+    for metric in &metrics {
+        assert_eq!(metric.distance(&empty, &empty, &sets), 0.);
+    }
+
+    // Only node 4's activation differs, 3 × 1 over 4 nodes for the node metrics
+    let gaussian = genome(&[], &[4], &[]);
+
+    assert_close(NeatDistance.distance(&empty, &gaussian, &sets), 3.);
+    assert_close(NodeGeneDistance.distance(&empty, &gaussian, &sets), 0.75);
+    assert_close(ParameterDistance.distance(&empty, &gaussian, &sets), 0.75);
+}
+
+#[test]
+fn genomes_without_matching_connections_are_infinitely_far_apart() {
+    let sets = sets();
+    let (a, _) = pair();
+    let empty = genome(&[], &[], &[]);
+    let unrelated = genome(&[], &[], &[(20, 1, 4, 1.)]);
+    let metrics: [&dyn CompatibilityDistance; 3] =
+        [&NeatDistance, &NodeGeneDistance, &ParameterDistance];
+
+    for metric in &metrics {
+        assert_eq!(metric.distance(&a, &unrelated, &sets), f64::INFINITY);
+        assert_eq!(metric.distance(&a, &empty, &sets), f64::INFINITY);
+        assert_eq!(metric.distance(&empty, &a, &sets), f64::INFINITY);
+    }
+}
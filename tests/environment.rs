@@ -0,0 +1,113 @@
+use hyper_darwin::{
+    Aggregation, Environment, EpisodeRunner, Genome, Space, Step, DEFAULT_MAX_STEPS,
+};
+
+// Pays the next of `returns` on the first step of each episode. Episodes end
+// there unless `endless` is set.
+struct Scripted {
+    returns: Vec<f64>,
+    episodes: usize,
+    endless: bool,
+}
+
+impl Scripted {
+    fn new(returns: &[f64]) -> Self {
+        Self {
+            returns: returns.to_vec(),
+            episodes: 0,
+            endless: false,
+        }
+    }
+
+    fn endless() -> Self {
+        Self {
+            endless: true,
+            ..Self::new(&[0.])
+        }
+    }
+}
+
+impl Environment for Scripted {
+    fn observation_space(&self) -> Space {
+        Space::uniform(1, 0., 1.)
+    }
+
+    fn action_space(&self) -> Space {
+        Space::uniform(1, -1., 1.)
+    }
+
+    fn reset(&mut self) -> Vec<f64> {
+        self.episodes += 1;
+        vec![0.]
+    }
+
+    fn step(&mut self, _action: &[f64]) -> Step {
+        let reward = self.returns[(self.episodes - 1) % self.returns.len()];
+
+        Step {
+            observation: vec![0.],
+            reward: if self.endless { 0. } else { reward },
+            done: !self.endless,
+        }
+    }
+}
+
+#[test]
+fn endless_episodes_are_capped() {
+    let genome = Genome::new(1, 1, false);
+
+    let episode = EpisodeRunner::new()
+        .episode(&mut Scripted::endless(), &genome)
+        .unwrap();
+    assert_eq!(episode.steps, DEFAULT_MAX_STEPS);
+
+    let episode = EpisodeRunner::new()
+        .max_steps(5)
+        .episode(&mut Scripted::endless(), &genome)
+        .unwrap();
+    assert_eq!(episode.steps, 5);
+}
+
+#[test]
+fn episodes_are_aggregated_per_trial() {
+    let genome = Genome::new(1, 1, false);
+
+    for &(aggregation, expected) in &[
+        (Aggregation::Mean, 3.),
+        (Aggregation::Sum, 9.),
+        (Aggregation::Min, 1.),
+    ] {
+        let runner = EpisodeRunner::new().episodes(3).aggregation(aggregation);
+        let mut env = Scripted::new(&[2., 6., 1.]);
+
+        assert_eq!(
+            runner.trial_returns(&mut env, &genome).unwrap(),
+            vec![expected]
+        );
+        assert_eq!(env.episodes, 3);
+    }
+}
+
+#[test]
+fn trials_are_averaged() {
+    let genome = Genome::new(1, 1, false);
+
+    // Two trials of two episodes, returning 1 and 3 then 5 and 7
+    for &(aggregation, trials, fitness) in &[
+        (Aggregation::Mean, [2., 6.], 4.),
+        (Aggregation::Sum, [4., 12.], 8.),
+        (Aggregation::Min, [1., 5.], 3.),
+    ] {
+        let runner = EpisodeRunner::new()
+            .episodes(2)
+            .trials(2)
+            .aggregation(aggregation);
+
+        let mut env = Scripted::new(&[1., 3., 5., 7.]);
+        assert_eq!(runner.trial_returns(&mut env, &genome).unwrap(), trials);
+
+        let mut env = Scripted::new(&[1., 3., 5., 7.]);
+        assert_eq!(runner.run(&mut env, &genome).unwrap(), fitness);
+        assert_eq!(env.episodes, 4);
+    }
+}
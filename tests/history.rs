@@ -0,0 +1,73 @@
+use hyper_darwin::{Activations, Connection, Genome, History, Node};
+
+// Inputs 1 and 2, the bias 3 and the output 4, connected by innovations 1 to 3
+fn history() -> History {
+    History::new(2, 1)
+}
+
+fn node(innov: u32) -> Node {
+    Node::new(innov, 0.5, 0.5, Activations::Linear)
+}
+
+fn conn(from: u32, to: u32) -> Connection {
+    Connection::new(0, from, to, 1., true)
+}
+
+fn innovs(hist: &mut History, from: u32, to: u32) -> (u32, u32, u32) {
+    let split = hist.mutate_node(&conn(from, to));
+    (split.node, split.in_conn, split.out_conn)
+}
+
+#[test]
+fn the_same_split_shares_its_innovations() {
+    let mut hist = history();
+
+    assert_eq!(innovs(&mut hist, 1, 4), (5, 4, 5));
+    assert_eq!(innovs(&mut hist, 2, 4), (6, 6, 7));
+    assert_eq!(innovs(&mut hist, 1, 4), (5, 4, 5));
+
+    // The halves of the split are known connections too
+    assert_eq!(hist.mutate_conn(&node(1), &node(5)), 4);
+    assert_eq!(hist.mutate_conn(&node(5), &node(4)), 5);
+    assert_eq!(hist.mutate_conn(&node(2), &node(5)), 8);
+    assert_eq!(hist.mutate_conn(&node(2), &node(5)), 8);
+}
+
+#[test]
+fn reset_tracking_hands_out_new_innovations() {
+    let mut hist = history();
+
+    assert_eq!(innovs(&mut hist, 1, 4), (5, 4, 5));
+    assert_eq!(hist.mutate_conn(&node(1), &node(4)), 1);
+
+    hist.reset_tracking();
+
+    // A generation later the same mutations are new innovations, numbered on
+    // from where the counters were
+    assert_eq!(innovs(&mut hist, 1, 4), (6, 6, 7));
+    assert_eq!(hist.mutate_conn(&node(1), &node(4)), 8);
+
+    // And shared again within that generation
+    assert_eq!(innovs(&mut hist, 1, 4), (6, 6, 7));
+    assert_eq!(hist.mutate_conn(&node(1), &node(4)), 8);
+}
+
+#[test]
+fn included_genomes_keep_their_innovations() {
+    // Node 5 and connections 4 and 5 were handed out by another History
+    let mut genome = Genome::new(2, 1, false);
+    genome.nodes.push(node(5));
+    genome.conns.push(Connection::new(4, 3, 5, 1., true));
+    genome.conns.push(Connection::new(9, 5, 4, 1., true));
+
+    let mut hist = history();
+    hist.include(&genome);
+
+    // Its connections keep their innovations
+    assert_eq!(hist.mutate_conn(&node(3), &node(5)), 4);
+    assert_eq!(hist.mutate_conn(&node(5), &node(4)), 9);
+
+    // New genes are numbered past all of its own
+    assert_eq!(innovs(&mut hist, 1, 4), (6, 10, 11));
+    assert_eq!(hist.mutate_conn(&node(1), &node(5)), 12);
+}
@@ -0,0 +1,121 @@
+use hyper_darwin::{Genome, HyperDarwinError, InteractiveEvolution, Settings};
+
+use serde_json::Value;
+
+use std::env;
+use std::fs;
+
+fn session() -> InteractiveEvolution {
+    InteractiveEvolution::new(Settings::new(8).direct(2, 1).node_mut_rate(0.5))
+}
+
+fn genes(genome: &Genome) -> Value {
+    serde_json::to_value(genome).unwrap()
+}
+
+#[test]
+fn bad_selections_leave_the_selection_alone() {
+    let mut session = session();
+    session.select_only(&[1, 3]).unwrap();
+
+    match session.select_only(&[0, 8]) {
+        Err(HyperDarwinError::SelectionOutOfRange { index: 8, len: 8 }) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert!(session.select(12).is_err());
+    assert_eq!(session.selected(), &[1, 3]);
+}
+
+#[test]
+fn a_generation_needs_a_selection() {
+    let mut session = session();
+    let before = session.genomes().iter().map(|g| g.id).collect::<Vec<_>>();
+
+    assert!(matches!(
+        session.next_generation(),
+        Err(HyperDarwinError::NothingSelected)
+    ));
+
+    assert_eq!(session.generations, 0);
+    assert_eq!(
+        session.genomes().iter().map(|g| g.id).collect::<Vec<_>>(),
+        before
+    );
+}
+
+#[test]
+fn parents_carry_over_unchanged() {
+    hyper_darwin::seed(31);
+
+    let mut session = session();
+    session.select_only(&[5, 2, 5]).unwrap();
+
+    let parents = [genes(&session.genomes()[5]), genes(&session.genomes()[2])];
+
+    session.next_generation().unwrap();
+
+    let genomes = session.genomes();
+
+    assert_eq!(session.generations, 1);
+    assert_eq!(genomes.len(), 8);
+    assert!(session.selected().is_empty());
+
+    // In selection order, in front of their offspring
+    assert_eq!(genes(&genomes[0]), parents[0]);
+    assert_eq!(genes(&genomes[1]), parents[1]);
+    assert!(genomes[2..].iter().all(|g| g.birth_generation == 1));
+}
+
+#[test]
+fn branching_needs_a_matching_genome() {
+    let mut session = session();
+
+    for &(inputs, outputs) in &[(3, 1), (2, 2)] {
+        match session.branch(&Genome::new(inputs, outputs, false)) {
+            Err(HyperDarwinError::IncompatibleGenome { expected, actual }) => {
+                assert_eq!(expected, (2, 1));
+                assert_eq!(actual, (inputs, outputs));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    assert_eq!(session.generations, 0);
+
+    let root = Genome::new(2, 1, false);
+    session.branch(&root).unwrap();
+
+    assert_eq!(genes(&session.genomes()[0]), genes(&root));
+}
+
+#[test]
+fn sessions_survive_save_and_load() {
+    hyper_darwin::seed(32);
+
+    let mut session = session();
+    session.select_only(&[0, 4]).unwrap();
+    session.next_generation().unwrap();
+    session.select_only(&[6, 1]).unwrap();
+
+    let path = env::temp_dir().join(format!(
+        "hyper_darwin_interactive_{}.json",
+        std::process::id()
+    ));
+    session.save(&path).unwrap();
+
+    let mut loaded = InteractiveEvolution::load(&path).unwrap();
+    fs::remove_file(&path).ok();
+
+    assert_eq!(loaded.generations, 1);
+    assert_eq!(loaded.selected(), &[6, 1]);
+    assert_eq!(
+        loaded.genomes().iter().map(genes).collect::<Vec<_>>(),
+        session.genomes().iter().map(genes).collect::<Vec<_>>()
+    );
+
+    // The loaded session picks up where the saved one stopped
+    loaded.next_generation().unwrap();
+    assert_eq!(genes(&loaded.genomes()[0]), genes(&session.genomes()[6]));
+    assert_eq!(loaded.generations, 2);
+}
@@ -0,0 +1,61 @@
+use hyper_darwin::{Genealogy, Genome};
+
+fn genome(id: u64, parents: &[u64]) -> Genome {
+    let mut genome = Genome::new(1, 1, false);
+    genome.id = id;
+    genome.parents = parents.to_vec();
+    genome
+}
+
+#[test]
+fn ancestors_come_nearest_first_and_once() {
+    // 1 and 2 are the parents of 3 and 4, which are the parents of 5, and 6
+    // has 5 and a parent that was never recorded
+    let mut genealogy = Genealogy::new();
+
+    for (id, parents) in &[
+        (1, vec![]),
+        (2, vec![]),
+        (3, vec![1, 2]),
+        (4, vec![2, 1]),
+        (5, vec![3, 4]),
+        (6, vec![5, 99]),
+    ] {
+        genealogy.record(&genome(*id, parents), 0);
+    }
+
+    assert_eq!(genealogy.ancestors(6), vec![5, 3, 4, 1, 2]);
+    assert_eq!(genealogy.ancestors(4), vec![2, 1]);
+    assert!(genealogy.ancestors(1).is_empty());
+    assert!(genealogy.ancestors(42).is_empty());
+}
+
+#[test]
+fn ancestors_of_a_deep_genealogy() {
+    let mut genealogy = Genealogy::new();
+    let depth = 20_000;
+
+    genealogy.record(&genome(0, &[]), 0);
+
+    for id in 1..depth {
+        genealogy.record(&genome(id, &[id - 1]), id);
+    }
+
+    let ancestors = genealogy.ancestors(depth - 1);
+
+    assert_eq!(ancestors.len() as u64, depth - 1);
+    assert!(ancestors.iter().rev().copied().eq(0..depth - 1));
+}
+
+// Only the last id is refused, reserving it would leave none for new Genomes
+#[test]
+fn loaded_ids_leave_room_for_new_ones() {
+    let mut json = serde_json::to_value(Genome::new(1, 1, false)).unwrap();
+
+    json["id"] = u64::MAX.into();
+    assert!(serde_json::from_value::<Genome>(json.clone()).is_err());
+
+    json["id"] = 7.into();
+    assert_eq!(serde_json::from_value::<Genome>(json).unwrap().id, 7);
+    assert!(Genome::new(1, 1, false).id > 7);
+}
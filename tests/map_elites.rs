@@ -0,0 +1,132 @@
+use hyper_darwin::{Dimension, HyperDarwinError, MapElites, Settings};
+
+use serde_json::Value;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("hyper_darwin_{}_{}.json", name, std::process::id()))
+}
+
+#[test]
+fn dimensions_need_a_range() {
+    assert!(Dimension::new(0., 1., 10).is_ok());
+
+    for &(min, max) in &[(1., 1.), (2., 1.), (f64::NAN, 1.), (0., f64::INFINITY)] {
+        match Dimension::new(min, max, 10) {
+            Err(HyperDarwinError::InvalidDimension { .. }) => {}
+            other => panic!("{} to {} gave {:?}", min, max, other),
+        }
+    }
+
+    assert!(matches!(
+        Dimension::new(0., 1., 0),
+        Err(HyperDarwinError::ZeroResolution)
+    ));
+
+    // Built by hand, past the checks of Dimension::new
+    let flat = Dimension {
+        min: 0.,
+        max: 1.,
+        resolution: 0,
+    };
+
+    assert!(matches!(
+        MapElites::new(Settings::new(1).direct(2, 1), vec![flat]),
+        Err(HyperDarwinError::ZeroResolution)
+    ));
+}
+
+#[test]
+fn archive_survives_save_and_load() {
+    hyper_darwin::seed(12);
+
+    let dims = vec![
+        Dimension::new(0., 1., 5).unwrap(),
+        Dimension::new(-1., 1., 4).unwrap(),
+    ];
+
+    let mut map = MapElites::new(Settings::new(1).direct(2, 1), dims).unwrap();
+
+    for _ in 0..5 {
+        let mut batch = map.ask(20);
+
+        for (i, genome) in batch.iter_mut().enumerate() {
+            genome.set_behavior(vec![i as f64 / 20., (i % 7) as f64 / 3. - 1.]);
+            genome.add_fitness(genome.conns.len() as f64 / (1. + i as f64));
+        }
+
+        map.tell(batch);
+    }
+
+    let path = temp_path("map");
+    map.save(&path).unwrap();
+
+    let mut loaded = MapElites::load(&path).unwrap();
+    fs::remove_file(&path).ok();
+
+    assert_eq!(loaded.dimensions(), map.dimensions());
+    assert_eq!(loaded.filled_cells(), map.filled_cells());
+    assert_eq!(loaded.evaluations, map.evaluations);
+    assert_eq!(loaded.qd_score(), map.qd_score());
+
+    for (coords, genome) in map.elites() {
+        assert_eq!(loaded.get(&coords).unwrap().id, genome.id);
+    }
+
+    assert_eq!(loaded.ask(10).len(), 10);
+}
+
+// Loads a saved empty 3×2 archive after `corrupt` edited its JSON
+fn load_corrupted<F: Fn(&mut Value)>(
+    name: &str,
+    corrupt: F,
+) -> Result<MapElites, HyperDarwinError> {
+    let dims = vec![
+        Dimension::new(0., 1., 3).unwrap(),
+        Dimension::new(0., 1., 2).unwrap(),
+    ];
+    let map = MapElites::new(Settings::new(1).direct(2, 1), dims).unwrap();
+
+    let path = temp_path(name);
+    map.save(&path).unwrap();
+
+    let mut json: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    corrupt(&mut json);
+    fs::write(&path, json.to_string()).unwrap();
+
+    let loaded = MapElites::load(&path);
+    fs::remove_file(&path).ok();
+
+    loaded
+}
+
+#[test]
+fn corrupted_archives_are_refused() {
+    assert!(load_corrupted("intact", |_| {}).is_ok());
+
+    let zero = load_corrupted("zero", |json| json["dims"][0]["resolution"] = 0.into());
+    assert!(matches!(zero, Err(HyperDarwinError::ZeroResolution)));
+
+    let inverted = load_corrupted("inverted", |json| json["dims"][1]["min"] = 2.into());
+    assert!(matches!(
+        inverted,
+        Err(HyperDarwinError::InvalidDimension { .. })
+    ));
+
+    // Resolutions that no longer match the number of cells
+    let resized = load_corrupted("resized", |json| json["dims"][0]["resolution"] = 4.into());
+    assert!(matches!(resized, Err(HyperDarwinError::Serialization(_))));
+
+    let truncated = load_corrupted("truncated", |json| {
+        json["archive"].as_array_mut().unwrap().pop();
+    });
+    assert!(matches!(truncated, Err(HyperDarwinError::Serialization(_))));
+
+    let huge = load_corrupted("huge", |json| {
+        json["dims"][0]["resolution"] = u64::MAX.into();
+    });
+    assert!(matches!(huge, Err(HyperDarwinError::TooManyCells)));
+}
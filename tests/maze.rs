@@ -0,0 +1,152 @@
+use hyper_darwin::tasks::{Maze, MazeNavigation, MazeNavigator, Task};
+use hyper_darwin::{Genome, HyperDarwinError, HyperSettings};
+
+// A 150×150 box with the robot at (100, 50) facing +x
+fn boxed(goal: (f64, f64)) -> MazeNavigator {
+    let text = format!(
+        "# a box\nstart 100 50 0\ngoal {} {}\n\
+         wall 0 0 150 0\nwall 150 0 150 150\nwall 150 150 0 150\nwall 0 150 0 0\n",
+        goal.0, goal.1
+    );
+
+    MazeNavigator::new(Maze::parse(&text).unwrap())
+}
+
+fn assert_close(a: &[f64], b: &[f64]) {
+    assert_eq!(a.len(), b.len());
+
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() < 1e-9, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn rangefinders_measure_the_nearest_wall() {
+    let mut navigator = boxed((20., 20.));
+    let diagonal = 50. * 2f64.sqrt() / 100.;
+
+    // -90, -45, 0, 45, 90 and -180 degrees off the heading, capped at 100
+    assert_close(
+        &navigator.rangefinders(),
+        &[0.5, diagonal, 0.5, diagonal, 1., 1.],
+    );
+
+    navigator.heading = 90.;
+
+    assert_close(&navigator.rangefinders(), &[0.5, diagonal, 1., 1., 1., 0.5]);
+}
+
+#[test]
+fn radar_points_at_the_goal() {
+    // Ahead, 90 degrees round, behind and 90 degrees the other way
+    for &(goal, slice) in &[
+        ((140., 50.), 0),
+        ((100., 140.), 1),
+        ((10., 50.), 2),
+        ((100., 10.), 3),
+    ] {
+        let mut expected = vec![0.; 4];
+        expected[slice] = 1.;
+
+        assert_eq!(boxed(goal).radar(), expected);
+    }
+
+    let mut navigator = boxed((140., 50.));
+    navigator.heading = 180.;
+
+    assert_eq!(navigator.radar(), vec![0., 0., 1., 0.]);
+}
+
+#[test]
+fn the_robot_stops_short_of_walls() {
+    let mut navigator = boxed((20., 20.));
+
+    for _ in 0..40 {
+        navigator.act(&[0., 1.]);
+        assert!(150. - navigator.position.0 >= 8.);
+    }
+
+    // It got there and stayed put, still facing the wall
+    assert!(navigator.position.0 > 139.);
+    assert_eq!(navigator.position.1, 50.);
+    assert_eq!(navigator.steps, 40);
+}
+
+fn parse_error(text: &str) -> (usize, String) {
+    match Maze::parse(text) {
+        Err(HyperDarwinError::InvalidMaze { line, reason }) => (line, reason),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    let valid = "start 0 0 0\ngoal 1 1\n";
+
+    assert_eq!(
+        parse_error(&format!("{}\nwall 0 0 1\n", valid)),
+        (4, String::from("wall takes x1, y1, x2 and y2"))
+    );
+    assert_eq!(
+        parse_error("start 0 0\n"),
+        (1, String::from("start takes x, y and a heading"))
+    );
+    assert_eq!(
+        parse_error(&format!("{}door 1 2\n", valid)),
+        (3, String::from("expected start, goal or wall"))
+    );
+    assert_eq!(
+        parse_error("goal one two\n"),
+        (1, String::from("expected numbers after the keyword"))
+    );
+    assert_eq!(
+        parse_error("start 0 0 0\n"),
+        (0, String::from("the maze has no goal"))
+    );
+    assert_eq!(
+        parse_error("goal 0 0\n"),
+        (0, String::from("the maze has no start"))
+    );
+}
+
+#[test]
+fn parsed_mazes_round_trip_through_configs() {
+    let task = MazeNavigation {
+        maze: boxed((20., 20.)).maze,
+        ..MazeNavigation::default()
+    };
+
+    let json = serde_json::to_string(&task).unwrap();
+    let back: MazeNavigation = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.maze, task.maze);
+
+    let text = toml::to_string(&task).unwrap();
+    let back: MazeNavigation = toml::from_str(&text).unwrap();
+    assert_eq!(back.maze, task.maze);
+}
+
+#[test]
+fn named_mazes_serialize_as_their_name() {
+    let json = serde_json::to_value(Maze::hard()).unwrap();
+    assert_eq!(json, serde_json::json!("hard"));
+
+    let back: Maze = serde_json::from_value(json).unwrap();
+    assert_eq!(back, Maze::hard());
+
+    assert!(serde_json::from_str::<Maze>("\"no/such/maze.txt\"").is_err());
+}
+
+#[test]
+fn one_run_gives_fitness_and_behavior() {
+    hyper_darwin::seed(2);
+
+    let task = MazeNavigation::default();
+    let hyper = HyperSettings::default();
+    let genome = Genome::new(10, 2, false);
+
+    let (fitness, behavior) = task.evaluate_with_behavior(&genome, &hyper);
+
+    assert_eq!(fitness, task.evaluate(&genome, &hyper));
+    assert_eq!(behavior, task.behavior(&genome, &hyper));
+    assert!(fitness > 0.);
+}
@@ -0,0 +1,111 @@
+use hyper_darwin::{FitnessMode, Genome, NoveltyArchive, Population, Settings, Species};
+
+fn with_behaviors(behaviors: &[Vec<f64>]) -> Vec<Genome> {
+    behaviors
+        .iter()
+        .map(|b| {
+            let mut genome = Genome::new(2, 1, false);
+            genome.set_behavior(b.clone());
+            genome
+        })
+        .collect()
+}
+
+// Fitness in the hundreds would drown novelty unless both are scaled first
+#[test]
+fn hybrid_scales_fitness_and_novelty() {
+    hyper_darwin::seed(13);
+
+    let sets = Settings::new(20)
+        .direct(2, 1)
+        .global_elitism(1)
+        .fitness_mode(FitnessMode::Hybrid(0.5));
+
+    let mut pop = Population::new(sets);
+
+    for (i, genome) in pop.get_citizens().iter_mut().enumerate() {
+        let behavior = if i == 0 { 10. } else { i as f64 * 0.01 };
+
+        genome.set_behavior(vec![behavior]);
+        genome.add_fitness(100. + i as f64);
+    }
+
+    let outlier = pop.citizens()[0].id;
+
+    pop.next_generation();
+
+    assert_eq!(pop.citizens()[0].id, outlier);
+}
+
+#[test]
+fn threshold_adapts_as_configured() {
+    let sets = Settings::new(10)
+        .novelty_k(1)
+        .novelty_max_additions(2)
+        .novelty_threshold_raise(2.)
+        .novelty_stale_generations(2)
+        .novelty_threshold_decay(0.5);
+
+    let mut archive = NoveltyArchive::new(0.1);
+
+    // Far apart, all of them get in
+    let mut spread = with_behaviors(&[vec![0.], vec![1.], vec![2.], vec![3.]]);
+    archive.evaluate(&mut spread, &sets);
+
+    assert_eq!(archive.len(), 4);
+    assert_eq!(archive.threshold, 0.2);
+
+    // Identical to each other and to archived behaviors, none get in
+    let mut crowded = with_behaviors(&[vec![0.], vec![0.]]);
+    archive.evaluate(&mut crowded, &sets);
+    assert_eq!(archive.threshold, 0.2);
+
+    archive.evaluate(&mut crowded, &sets);
+    assert_eq!(archive.threshold, 0.1);
+    assert_eq!(archive.len(), 4);
+}
+
+#[test]
+fn behaviors_of_another_length_are_skipped() {
+    let sets = Settings::new(4).novelty_k(1);
+    let mut archive = NoveltyArchive::new(0.5);
+
+    let mut population = with_behaviors(&[vec![0., 0.], vec![3., 4.], vec![7.], vec![]]);
+    archive.evaluate(&mut population, &sets);
+
+    let novelty = population.iter().map(|g| g.novelty).collect::<Vec<f64>>();
+
+    assert_eq!(novelty, vec![5., 5., 0., 0.]);
+    assert_eq!(archive.len(), 2);
+    assert!(archive.behaviors().all(|b| b.len() == 2));
+
+    // Once archived, the archive's length wins even when outnumbered
+    let mut population = with_behaviors(&[vec![1.], vec![2.], vec![0., 1.]]);
+    archive.evaluate(&mut population, &sets);
+
+    assert_eq!(population[0].novelty, 0.);
+    assert_eq!(population[1].novelty, 0.);
+    assert_eq!(population[2].novelty, 1.);
+}
+
+#[test]
+fn stagnancy_follows_objective_fitness() {
+    let mut genome = Genome::new(2, 1, false);
+    genome.objective_fitness = 1.;
+    genome.fitness = 1.;
+
+    let mut species = Species::new(0, genome, 0);
+
+    // Novelty rewrote the fitness upwards, but the objective didn't improve
+    species.genomes[0].fitness = 10.;
+    species.update_stagnancy();
+
+    assert_eq!(species.stagnancy, 1);
+    assert_eq!(species.max_fitness(), 1.);
+
+    species.genomes[0].objective_fitness = 2.;
+    species.update_stagnancy();
+
+    assert_eq!(species.stagnancy, 0);
+    assert_eq!(species.max_fitness(), 2.);
+}
@@ -0,0 +1,159 @@
+use hyper_darwin::pareto::{crowding_distance, dominates, non_dominated_sort};
+use hyper_darwin::{FitnessMode, HyperDarwinError, ParetoScope, Population, Settings};
+
+#[test]
+fn domination() {
+    assert!(dominates(&[2., 1.], &[1., 1.]));
+    assert!(!dominates(&[1., 1.], &[1., 1.]));
+    assert!(!dominates(&[2., 0.], &[1., 1.]));
+    assert!(!dominates(&[1., 1.], &[2., 1.]));
+}
+
+#[test]
+fn ranking_needs_matching_objectives() {
+    let sets = Settings::new(4)
+        .direct(2, 1)
+        .fitness_mode(FitnessMode::MultiObjective);
+
+    let mut pop = Population::new(sets);
+
+    for genome in pop.get_citizens().iter_mut() {
+        genome.set_objectives(vec![1., 1.]);
+    }
+
+    pop.get_citizens()[2].set_objectives(vec![0., 0., 0.]);
+
+    match pop.try_next_generation() {
+        Err(HyperDarwinError::ObjectiveCountMismatch {
+            genome,
+            expected: 2,
+            actual: 3,
+        }) => assert_eq!(pop.citizens()[genome].objectives.len(), 3),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert_eq!(pop.generations, 0);
+
+    // Fixed, the same evaluation goes through
+    for genome in pop.get_citizens().iter_mut() {
+        genome.set_objectives(vec![1., 1.]);
+    }
+
+    pop.try_next_generation().unwrap();
+    assert_eq!(pop.generations, 1);
+}
+
+#[test]
+fn fronts() {
+    let points: [&[f64]; 6] = [
+        &[1., 5.],
+        &[5., 1.],
+        &[3., 3.],
+        &[2., 2.],
+        &[1., 1.],
+        &[4., 0.],
+    ];
+
+    let mut fronts = non_dominated_sort(&points);
+    fronts.iter_mut().for_each(|f| f.sort_unstable());
+
+    assert_eq!(fronts, vec![vec![0, 1, 2], vec![3, 5], vec![4]]);
+}
+
+#[test]
+fn crowding() {
+    let points: [&[f64]; 4] = [&[0., 4.], &[1., 3.], &[3., 1.], &[4., 0.]];
+
+    let distances = crowding_distance(&points, &[0, 1, 2, 3]);
+
+    assert!(distances[0].is_infinite());
+    assert!(distances[3].is_infinite());
+    // (3 - 0) / 4 for each objective
+    assert_eq!(distances[1], 1.5);
+    assert_eq!(distances[2], 1.5);
+
+    let pair = crowding_distance(&points, &[1, 2]);
+    assert!(pair.iter().all(|d| d.is_infinite()));
+}
+
+// Fronts are computed over the whole population, whatever species the Genomes
+// end up in
+#[test]
+fn population_wide_fronts() {
+    hyper_darwin::seed(7);
+
+    let sets = Settings::new(30)
+        .direct(2, 1)
+        .speciation_threshold(0.1)
+        .fitness_mode(FitnessMode::MultiObjective);
+
+    let mut pop = Population::new(sets);
+
+    let objectives = (0..30)
+        .map(|i| {
+            let x = (i % 10) as f64;
+            let y = ((i * 7) % 11) as f64;
+            vec![x, y]
+        })
+        .collect::<Vec<Vec<f64>>>();
+
+    for (genome, objectives) in pop.get_citizens().iter_mut().zip(&objectives) {
+        genome.set_objectives(objectives.clone());
+        genome.add_fitness(objectives[0]);
+    }
+
+    pop.next_generation();
+
+    let mut front = pop
+        .pareto_front()
+        .iter()
+        .map(|g| g.objectives.clone())
+        .collect::<Vec<Vec<f64>>>();
+
+    let mut expected = objectives
+        .iter()
+        .filter(|a| !objectives.iter().any(|b| dominates(b, a)))
+        .cloned()
+        .collect::<Vec<Vec<f64>>>();
+
+    front.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    assert_eq!(front, expected);
+    assert!(pop.species().len() + pop.extinct_species().len() > 1);
+}
+
+// Ranked on their own, every species' best Genome is on its species' front,
+// even where the whole population has better ones
+#[test]
+fn species_wide_fronts() {
+    for &scope in &[ParetoScope::Population, ParetoScope::Species] {
+        hyper_darwin::seed(7);
+
+        let sets = Settings::new(30)
+            .direct(2, 1)
+            .speciation_threshold(1.5)
+            .fitness_mode(FitnessMode::MultiObjective)
+            .pareto_scope(scope);
+
+        let mut pop = Population::new(sets);
+
+        for (i, genome) in pop.get_citizens().iter_mut().enumerate() {
+            genome.set_objectives(vec![(i % 10) as f64, ((i * 7) % 11) as f64]);
+        }
+
+        pop.next_generation();
+
+        let heads = pop
+            .species()
+            .iter()
+            .filter_map(|s| s.genomes.first())
+            .collect::<Vec<_>>();
+
+        assert!(heads.len() > 1);
+
+        let all_on_front = heads.iter().all(|g| g.pareto_rank == 0);
+
+        assert_eq!(all_on_front, scope == ParetoScope::Species);
+    }
+}
@@ -0,0 +1,221 @@
+use hyper_darwin::{
+    Evolvable, FitnessMode, History, HyperDarwinError, Population, Settings, Speciation,
+};
+
+// Fitness rises with the index while the behaviors crowd together at the top,
+// so novelty favors the least fit Genomes
+fn novelty_population(mode: FitnessMode) -> Population {
+    hyper_darwin::seed(4);
+
+    let sets = Settings::new(20)
+        .direct(2, 1)
+        .speciation_threshold(100.)
+        .fitness_mode(mode);
+
+    let mut pop = Population::new(sets);
+
+    for (i, genome) in pop.get_citizens().iter_mut().enumerate() {
+        genome.add_fitness(i as f64 / 20.);
+        genome.set_behavior(vec![(i as f64).sqrt()]);
+        genome.set_objectives(vec![i as f64, -(i as f64)]);
+    }
+
+    pop
+}
+
+#[test]
+fn champions_keep_the_objective_fitness() {
+    for mode in &[
+        FitnessMode::Novelty,
+        FitnessMode::Hybrid(0.5),
+        FitnessMode::MultiObjective,
+    ] {
+        let mut pop = novelty_population(*mode);
+        pop.next_generation();
+
+        assert_eq!(pop.generation_champions()[0].fitness, 0.95);
+        assert_eq!(pop.hall_of_fame().entries()[0].fitness, 0.95);
+        assert_eq!(pop.species_champions()[0].fitness, 0.95);
+    }
+}
+
+#[test]
+fn species_history_keeps_the_objective_fitness() {
+    for mode in &[FitnessMode::Novelty, FitnessMode::MultiObjective] {
+        let mut pop = novelty_population(*mode);
+        pop.next_generation();
+
+        let species = pop.species().iter().chain(pop.extinct_species());
+        let snapshot = species.flat_map(|s| s.history()).next().unwrap();
+
+        assert_eq!(snapshot.size, 20);
+        assert_eq!(snapshot.max_fitness, 0.95);
+        assert!((snapshot.avg_fitness - 0.475).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn adaptive_clustering_of_identical_genomes() {
+    hyper_darwin::seed(5);
+
+    let sets = Settings::new(20)
+        .direct(2, 1)
+        .speciation(Speciation::AdaptiveKMedoids { min: 1, max: 4 });
+
+    let mut pop = Population::new(sets);
+    let first = pop.citizens()[0].clone();
+
+    for genome in pop.get_citizens().iter_mut() {
+        *genome = first.clone();
+        genome.add_fitness(0.5);
+    }
+
+    pop.next_generation();
+
+    assert_eq!(pop.citizens().len(), 20);
+    assert!(!pop.species().is_empty());
+}
+
+// A single number, so that distances are easy to reason about
+#[derive(Clone)]
+struct Point {
+    x: f64,
+    fitness: f64,
+}
+
+impl Evolvable for Point {
+    fn initial(_sets: &Settings) -> Self {
+        Point { x: 0., fitness: 0. }
+    }
+
+    fn mutate(&mut self, _hist: &mut History, _sets: &Settings) {}
+
+    fn crossover(parent1: &Self, _parent2: &Self, _sets: &Settings) -> Self {
+        parent1.clone()
+    }
+
+    fn offspring(&self) -> Self {
+        self.clone()
+    }
+
+    fn distance(&self, other: &Self, _sets: &Settings) -> f64 {
+        (self.x - other.x).abs()
+    }
+
+    fn fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
+
+#[test]
+fn adaptive_clustering_finds_separated_groups() {
+    hyper_darwin::seed(6);
+
+    let sets = Settings::new(20)
+        .speciation(Speciation::AdaptiveKMedoids { min: 1, max: 5 })
+        .allowed_stagnancy(100);
+
+    let mut pop = Population::<Point>::with_encoding(sets);
+
+    for (i, point) in pop.get_citizens().iter_mut().enumerate() {
+        let group = if i < 10 { 0. } else { 100. };
+        point.x = group + (i % 10) as f64 * 0.1;
+        point.fitness = 1.;
+    }
+
+    pop.next_generation();
+
+    assert_eq!(pop.species().len(), 2);
+
+    for species in pop.species() {
+        let first = species.genomes[0].x;
+        assert!(species.genomes.iter().all(|p| (p.x - first).abs() < 1.));
+    }
+}
+
+#[test]
+fn global_elites_are_not_copied_twice() {
+    hyper_darwin::seed(10);
+
+    let sets = Settings::new(30)
+        .direct(2, 1)
+        .global_elitism(3)
+        .elitism(2)
+        .elitism_min_species_size(1);
+
+    let mut pop = Population::new(sets);
+
+    for _ in 0..5 {
+        for (i, genome) in pop.get_citizens().iter_mut().enumerate() {
+            genome.add_fitness(1. + i as f64);
+        }
+
+        pop.next_generation();
+
+        let mut ids = pop.citizens().iter().map(|g| g.id).collect::<Vec<u64>>();
+        ids.sort_unstable();
+        ids.dedup();
+
+        assert_eq!(ids.len(), 30);
+    }
+}
+
+#[test]
+fn global_elites_leave_room_for_offspring() {
+    assert!(Settings::new(10).global_elitism(9).validate().is_ok());
+    assert!(Settings::new(10).global_elitism(10).validate().is_err());
+
+    hyper_darwin::seed(11);
+
+    let mut pop = Population::new(Settings::new(10).direct(2, 1).global_elitism(10));
+
+    for genome in pop.get_citizens().iter_mut() {
+        genome.add_fitness(1.);
+    }
+
+    pop.next_generation();
+
+    assert_eq!(pop.citizens().len(), 10);
+    assert!(!pop.species().is_empty());
+}
+
+#[test]
+fn nan_fitness_does_not_panic() {
+    hyper_darwin::seed(8);
+
+    let mut pop = Population::new(Settings::new(10).direct(2, 1));
+
+    for (i, genome) in pop.get_citizens().iter_mut().enumerate() {
+        genome.fitness = if i % 3 == 0 { f64::NAN } else { i as f64 };
+    }
+
+    pop.next_generation();
+
+    assert_eq!(pop.citizens().len(), 10);
+}
+
+#[test]
+fn populations_need_genomes() {
+    assert!(matches!(
+        Population::try_new(Settings::new(0).direct(2, 1)),
+        Err(HyperDarwinError::InvalidSettings(_))
+    ));
+
+    let mut pop = Population::new(Settings::new(4).direct(2, 1));
+    pop.get_citizens().clear();
+
+    assert!(matches!(
+        pop.try_next_generation(),
+        Err(HyperDarwinError::EmptyPopulation)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "a Population needs a pop_size above 0")]
+fn an_empty_population_cannot_be_built() {
+    Population::new(Settings::new(0).direct(2, 1));
+}
@@ -0,0 +1,184 @@
+use hyper_darwin::tasks::{CartPole, Poles};
+use hyper_darwin::{
+    Activations, Connection, EpisodeRunner, Genome, History, Node, Population, Settings,
+};
+
+use std::collections::HashMap;
+
+// Connections that don't lead further right
+fn recurrent_conns(genome: &Genome) -> Vec<&Connection> {
+    let x = genome
+        .nodes
+        .iter()
+        .map(|n| (n.innov, n.x))
+        .collect::<HashMap<u32, f64>>();
+
+    genome
+        .conns
+        .iter()
+        .filter(|c| x[&c.from] >= x[&c.to])
+        .collect()
+}
+
+// Adds a Linear hidden node at x, keeping the nodes ordered by x
+fn add_node(genome: &mut Genome, innov: u32, x: f64) {
+    genome
+        .nodes
+        .push(Node::new(innov, x, 0.5, Activations::Linear));
+    genome.nodes.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+}
+
+// A Genome whose output is its input from the previous step. The input is
+// copied into node 4 and read back through a recurrent connection by node 5.
+fn delay() -> Genome {
+    let mut genome = Genome::new(1, 1, false);
+    genome.conns.iter_mut().for_each(|c| c.weight = 0.);
+
+    add_node(&mut genome, 4, 0.5);
+    add_node(&mut genome, 5, 0.25);
+
+    genome.conns.push(Connection::new(10, 1, 4, 1., true));
+    genome.conns.push(Connection::new(11, 4, 5, 1., true));
+    genome.conns.push(Connection::new(12, 5, 3, 1., true));
+
+    genome
+}
+
+// Undoes the output squashing
+fn unsquash(out: f64) -> f64 {
+    -((2. / (out + 1.)) - 1.).ln() / 4.9
+}
+
+#[test]
+fn recurrent_connections_carry_the_previous_step() {
+    let genome = delay();
+    let mut memory = HashMap::new();
+
+    let outputs = [0.3, -0.5, 0.1, 0.]
+        .iter()
+        .map(|&x| unsquash(genome.activate(&[x], &mut memory).unwrap()[0]))
+        .collect::<Vec<f64>>();
+
+    for (out, expected) in outputs.iter().zip(&[0., 0.3, -0.5, 0.1]) {
+        assert!((out - expected).abs() < 1e-9, "{} != {}", out, expected);
+    }
+}
+
+#[test]
+fn feed_forward_forgets_between_calls() {
+    let genome = delay();
+
+    for &x in &[0.3, -0.5] {
+        assert_eq!(genome.feed_forward(&[x]).unwrap(), 0.);
+    }
+}
+
+#[test]
+fn recurrent_connections_lead_back_into_hidden_and_output_nodes() {
+    hyper_darwin::seed(4);
+
+    let mut hist = History::new(2, 2);
+    let mut genome = Genome::new(2, 2, false);
+
+    while genome.add_recurrent_conn(&mut hist).is_ok() {}
+
+    // Both outputs into themselves and into each other, never into an input
+    // or the bias
+    let recurrent = recurrent_conns(&genome);
+
+    assert_eq!(recurrent.len(), 4);
+    assert!(recurrent.iter().all(|c| c.to == 4 || c.to == 5));
+}
+
+#[test]
+fn feed_forward_settings_add_no_recurrent_connections() {
+    hyper_darwin::seed(5);
+
+    let sets = Settings::new(1)
+        .direct(2, 1)
+        .conn_mut_rate(1.)
+        .node_mut_rate(0.5);
+    let mut hist = History::new(2, 1);
+    let mut genome = Genome::from_settings(&sets);
+
+    for _ in 0..50 {
+        genome.mutate(&mut hist, &sets);
+    }
+
+    assert!(recurrent_conns(&genome).is_empty());
+
+    let sets = sets.recurrent_conn_rate(1.);
+
+    for _ in 0..5 {
+        genome.mutate(&mut hist, &sets);
+    }
+
+    assert!(!recurrent_conns(&genome).is_empty());
+}
+
+// Observes only the cart position and pole angle, and estimates their
+// velocities from the previous step through recurrent connections
+fn velocity_estimator() -> Genome {
+    let mut genome = Genome::new(2, 1, false);
+
+    for (conn, &weight) in genome.conns.iter_mut().zip(&[4.5, 116., 0.]) {
+        conn.weight = weight;
+    }
+
+    for &(input, copy, previous, weight, innov) in &[(1, 5, 6, -4., 10), (2, 7, 8, -100., 20)] {
+        add_node(&mut genome, copy, 0.5);
+        add_node(&mut genome, previous, 0.25);
+
+        genome
+            .conns
+            .push(Connection::new(innov, input, copy, 1., true));
+        genome
+            .conns
+            .push(Connection::new(innov + 1, copy, previous, 1., true));
+        genome
+            .conns
+            .push(Connection::new(innov + 2, previous, 4, weight, true));
+    }
+
+    genome
+}
+
+#[test]
+fn a_recurrent_genome_balances_without_velocities() {
+    let genome = velocity_estimator();
+    let runner = EpisodeRunner::new().max_steps(100_000);
+
+    let mut cart = CartPole::new(Poles::Single, false);
+    assert_eq!(runner.run(&mut cart, &genome).unwrap(), 100_000.);
+
+    // Without its memory it drops the pole
+    let mut cart = CartPole::new(Poles::Single, false);
+    let steps = cart.balance(|obs| genome.feed_forward(obs).unwrap(), 100_000);
+    assert!(steps < 1000, "balanced {} steps", steps);
+}
+
+#[test]
+fn populations_evolve_recurrent_genomes() {
+    hyper_darwin::seed(6);
+
+    let sets = Settings::new(50)
+        .direct(2, 1)
+        .conn_mut_rate(0.5)
+        .recurrent_conn_rate(0.5);
+    let mut pop = Population::new(sets);
+
+    for _ in 0..10 {
+        for genome in pop.get_citizens().iter_mut() {
+            let mut memory = HashMap::new();
+            let out = genome.activate(&[1., 0.], &mut memory).unwrap()[0];
+            genome.add_fitness(out + genome.activate(&[0., 1.], &mut memory).unwrap()[0]);
+        }
+
+        pop.next_generation();
+    }
+
+    assert!(pop
+        .citizens()
+        .iter()
+        .any(|g| !recurrent_conns(g).is_empty()));
+}
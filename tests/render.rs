@@ -0,0 +1,226 @@
+use hyper_darwin::{
+    render_connectivity, Genome, History, HyperDarwinError, HyperSettings, Image, Settings,
+    Substrate,
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+fn be32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// Splits a PNG into its chunks, checking the signature and every CRC
+fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    assert_eq!(
+        &png[..8],
+        &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']
+    );
+
+    let mut chunks = Vec::new();
+    let mut rest = &png[8..];
+
+    while !rest.is_empty() {
+        let len = be32(rest) as usize;
+        let body = &rest[4..8 + len];
+
+        assert_eq!(crc32(body), be32(&rest[8 + len..]), "bad CRC");
+
+        chunks.push(([body[0], body[1], body[2], body[3]], body[4..].to_vec()));
+        rest = &rest[12 + len..];
+    }
+
+    chunks
+}
+
+// Undoes the stored deflate blocks of a zlib stream, checking the Adler-32
+fn inflate_stored(data: &[u8]) -> Vec<u8> {
+    assert_eq!(&data[..2], &[0x78, 0x01]);
+
+    let mut raw = Vec::new();
+    let mut pos = 2;
+
+    loop {
+        let last = data[pos] & 1 == 1;
+        assert_eq!(data[pos] >> 1, 0, "not a stored block");
+
+        let len = u16::from_le_bytes([data[pos + 1], data[pos + 2]]);
+        let nlen = u16::from_le_bytes([data[pos + 3], data[pos + 4]]);
+        assert_eq!(len, !nlen);
+
+        raw.extend_from_slice(&data[pos + 5..pos + 5 + len as usize]);
+        pos += 5 + len as usize;
+
+        if last {
+            break;
+        }
+    }
+
+    let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    assert_eq!(be32(&data[pos..]), b << 16 | a, "bad Adler-32");
+    assert_eq!(pos + 4, data.len());
+
+    raw
+}
+
+fn gradient(width: usize, height: usize, channels: usize) -> Image {
+    let mut image = Image::new(width, height, channels);
+
+    for (i, pixel) in image.pixels.iter_mut().enumerate() {
+        *pixel = (i % 251) as u8;
+    }
+
+    image
+}
+
+fn png(image: &Image) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    image.write_png(&mut bytes).unwrap();
+    bytes
+}
+
+fn decoded_pixels(image: &Image) -> Vec<u8> {
+    let chunks = chunks(&png(image));
+
+    assert_eq!(
+        chunks.iter().map(|(kind, _)| kind).collect::<Vec<_>>(),
+        vec![b"IHDR", b"IDAT", b"IEND"]
+    );
+
+    let header = &chunks[0].1;
+    let color_type = if image.channels == 1 { 0 } else { 2 };
+
+    assert_eq!(be32(&header[..4]) as usize, image.width);
+    assert_eq!(be32(&header[4..8]) as usize, image.height);
+    assert_eq!(&header[8..], &[8, color_type, 0, 0, 0]);
+
+    let raw = inflate_stored(&chunks[1].1);
+    let stride = image.width * image.channels + 1;
+
+    assert_eq!(raw.len(), stride * image.height);
+
+    raw.chunks(stride)
+        .flat_map(|line| {
+            assert_eq!(line[0], 0, "unfiltered scanlines expected");
+            line[1..].to_vec()
+        })
+        .collect()
+}
+
+#[test]
+fn png_round_trips_grayscale_and_rgb() {
+    for &(width, height, channels) in &[(1, 1, 1), (5, 3, 1), (4, 7, 3)] {
+        let image = gradient(width, height, channels);
+        assert_eq!(decoded_pixels(&image), image.pixels);
+    }
+}
+
+#[test]
+fn png_splits_large_images_into_several_blocks() {
+    // 300 scanlines of 601 bytes don't fit in one 65535 byte stored block
+    let image = gradient(200, 300, 3);
+    assert_eq!(decoded_pixels(&image), image.pixels);
+}
+
+#[test]
+fn ppm_has_a_header_and_raw_pixels() {
+    for &(channels, magic) in &[(1, "P5"), (3, "P6")] {
+        let image = gradient(3, 2, channels);
+        let mut bytes = Vec::new();
+        image.write_ppm(&mut bytes).unwrap();
+
+        let header = format!("{}\n3 2\n255\n", magic);
+
+        assert_eq!(&bytes[..header.len()], header.as_bytes());
+        assert_eq!(&bytes[header.len()..], &image.pixels[..]);
+    }
+}
+
+#[test]
+fn scaling_repeats_every_pixel() {
+    let image = gradient(2, 2, 3);
+    let scaled = image.scaled(3);
+
+    assert_eq!((scaled.width, scaled.height), (6, 6));
+
+    for y in 0..6 {
+        for x in 0..6 {
+            assert_eq!(scaled.pixel(x, y), image.pixel(x / 3, y / 3));
+        }
+    }
+}
+
+fn substrate() -> Substrate {
+    hyper_darwin::seed(3);
+
+    let sets = Settings::new(1).node_mut_rate(0.5).conn_mut_rate(0.5);
+    let mut hist = History::new(4, 1);
+    let mut genome = Genome::new(4, 1, false);
+
+    for _ in 0..20 {
+        genome.mutate(&mut hist, &sets);
+    }
+
+    Substrate::decode(
+        &genome,
+        3,
+        4,
+        &HyperSettings::default(),
+        None::<fn(f64, f64) -> f64>,
+    )
+    .unwrap()
+}
+
+#[test]
+fn connectivity_is_laid_out_like_the_output_plane() {
+    let substrate = substrate();
+    let image = render_connectivity(&substrate, (2, 3)).unwrap();
+
+    assert_eq!((image.width, image.height, image.channels), (4, 3, 3));
+
+    for row in 0..3 {
+        for col in 0..4 {
+            let w = substrate.weight((2, 3), (row, col));
+            let pixel = image.pixel(col, row);
+
+            // Red for positive weights, blue for negative ones, never both
+            assert_eq!(pixel[1], 0);
+            assert!(pixel[0] == 0 || w > 0.);
+            assert!(pixel[2] == 0 || w < 0.);
+        }
+    }
+}
+
+#[test]
+fn connectivity_refuses_points_outside_the_plane() {
+    let substrate = substrate();
+
+    for &from in &[(3, 0), (0, 4), (7, 9)] {
+        match render_connectivity(&substrate, from) {
+            Err(HyperDarwinError::PointOutOfPlane { point, plane }) => {
+                assert_eq!(point, from);
+                assert_eq!(plane, (3, 4));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use hyper_darwin::{HyperSettings, Settings, SettingsError};
+
+fn fields(errors: Vec<SettingsError>) -> Vec<&'static str> {
+    errors
+        .into_iter()
+        .map(|err| match err {
+            SettingsError::RateOutOfRange { field, .. }
+            | SettingsError::NotPositive { field, .. }
+            | SettingsError::Negative { field, .. }
+            | SettingsError::TooManyElites { field, .. } => field,
+            SettingsError::InvalidWeightRange { .. } => "weight range",
+            SettingsError::InvalidSpeciesRange { .. } => "species range",
+            SettingsError::EmptyActivationSet => "activations",
+        })
+        .collect()
+}
+
+#[test]
+fn defaults_are_valid() {
+    assert!(Settings::default().validate().is_ok());
+    assert!(HyperSettings::default().validate().is_ok());
+}
+
+#[test]
+fn weight_range_refuses_nan_and_infinity() {
+    let nan_min = HyperSettings::default().min_weight(f64::NAN);
+    assert_eq!(
+        fields(nan_min.validate().unwrap_err()),
+        vec!["min_weight", "weight range"]
+    );
+
+    let nan_max = HyperSettings::default().max_weight(f64::NAN);
+    assert_eq!(
+        fields(nan_max.validate().unwrap_err()),
+        vec!["weight range"]
+    );
+
+    let infinite_max = HyperSettings::default().max_weight(f64::INFINITY);
+    assert_eq!(
+        fields(infinite_max.validate().unwrap_err()),
+        vec!["weight range"]
+    );
+}
+
+#[test]
+fn elites_and_counts() {
+    let sets = Settings::new(10)
+        .elitism(10)
+        .global_elitism(12)
+        .elitism_min_species_size(0)
+        .novelty_k(0);
+
+    assert_eq!(
+        fields(sets.validate().unwrap_err()),
+        vec![
+            "elitism",
+            "global_elitism",
+            "elitism_min_species_size",
+            "novelty_k"
+        ]
+    );
+}
+
+#[test]
+fn rates_refuse_nan() {
+    let sets = Settings::default().conn_mut_rate(f64::NAN);
+
+    assert_eq!(fields(sets.validate().unwrap_err()), vec!["conn_mut_rate"]);
+}
@@ -0,0 +1,93 @@
+use hyper_darwin::{
+    Genome, History, HyperDarwinError, HyperSettings, HyperTensor, Settings, Substrate,
+};
+
+fn cppn(inputs: u32) -> Genome {
+    let sets = Settings::new(1).node_mut_rate(0.5).conn_mut_rate(0.5);
+    let mut hist = History::new(inputs, 1);
+    let mut genome = Genome::new(inputs, 1, false);
+
+    for _ in 0..20 {
+        genome.mutate(&mut hist, &sets);
+    }
+
+    genome
+}
+
+fn input() -> HyperTensor {
+    HyperTensor::new(vec![
+        vec![0., 1., 0., 0.5],
+        vec![0.25, 0., 0., 0.],
+        vec![0., 0., -1., 0.],
+    ])
+    .unwrap()
+}
+
+fn assert_close(a: &[Vec<f64>], b: &[Vec<f64>]) {
+    for (x, y) in a.iter().flatten().zip(b.iter().flatten()) {
+        assert!((x - y).abs() < 1e-9, "{} != {}", x, y);
+    }
+}
+
+#[test]
+fn streaming_matches_decoded_substrate() {
+    hyper_darwin::seed(8);
+
+    let sets = HyperSettings::default();
+    let genome = cppn(4);
+
+    let streamed = genome
+        .hyper_feed_forward(input(), &sets, None::<fn(f64, f64) -> f64>)
+        .unwrap();
+
+    let decoded = Substrate::decode(&genome, 3, 4, &sets, None::<fn(f64, f64) -> f64>)
+        .unwrap()
+        .activate(&input())
+        .unwrap();
+
+    assert_close(&streamed, &decoded);
+    assert!(streamed.iter().flatten().any(|&v| v != 0.));
+}
+
+#[test]
+fn streaming_with_a_third_parameter() {
+    hyper_darwin::seed(9);
+
+    let sets = HyperSettings::default();
+    let genome = cppn(6);
+    let distance = |x: f64, y: f64| (x * x + y * y).sqrt();
+
+    let streamed = genome
+        .hyper_feed_forward(input(), &sets, Some(distance))
+        .unwrap();
+
+    let decoded = Substrate::decode(&genome, 3, 4, &sets, Some(distance))
+        .unwrap()
+        .activate(&input())
+        .unwrap();
+
+    assert_close(&streamed, &decoded);
+}
+
+#[test]
+fn streaming_checks_the_cppn() {
+    let genome = Genome::new(4, 1, false);
+    let distance = |x: f64, y: f64| x + y;
+
+    match genome.hyper_feed_forward(input(), &HyperSettings::default(), Some(distance)) {
+        Err(HyperDarwinError::UnexpectedThirdParamFn) => {}
+        other => panic!("unexpected {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn a_genome_without_outputs_has_nothing_to_feed_forward() {
+    let genome = Genome::new(2, 0, false);
+
+    assert!(genome.feed_forward_all(&[1., 0.]).unwrap().is_empty());
+
+    match genome.feed_forward(&[1., 0.]) {
+        Err(HyperDarwinError::NoOutputs) => {}
+        other => panic!("unexpected {:?}", other),
+    }
+}
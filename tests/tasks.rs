@@ -0,0 +1,185 @@
+use hyper_darwin::tasks::{self, Parity, Retina, Task, Xor};
+use hyper_darwin::Encoding;
+use hyper_darwin::{
+    ActivationSet, Activations, Connection, Experiment, ExperimentConfig, Genome, HyperDarwinError,
+    HyperSettings, Node, Settings, Speciation,
+};
+
+use serde_json::Value;
+
+use std::env;
+
+// A Genome whose every connection weighs 0, so each output sits at 0, halfway
+// between the classes
+fn silent(inputs: u32, outputs: u32) -> Genome {
+    let mut genome = Genome::new(inputs, outputs, false);
+    genome.conns.iter_mut().for_each(|c| c.weight = 0.);
+    genome
+}
+
+// Exclusive or as OR and not AND, with two sigmoid hidden nodes
+fn xor_solver() -> Genome {
+    let mut genome =
+        Genome::with_activations(2, 1, false, &ActivationSet::Single(Activations::Sigmoid));
+
+    genome
+        .nodes
+        .insert(3, Node::new(5, 0.5, 0.3, Activations::Sigmoid));
+    genome
+        .nodes
+        .insert(4, Node::new(6, 0.5, 0.6, Activations::Sigmoid));
+
+    genome.conns = vec![
+        Connection::new(1, 1, 5, 2., true),
+        Connection::new(2, 2, 5, 2., true),
+        Connection::new(3, 3, 5, -1., true),
+        Connection::new(4, 1, 6, 2., true),
+        Connection::new(5, 2, 6, 2., true),
+        Connection::new(6, 3, 6, -3., true),
+        Connection::new(7, 5, 4, 10., true),
+        Connection::new(8, 6, 4, -10., true),
+        Connection::new(9, 3, 4, -5., true),
+    ];
+
+    genome
+}
+
+#[test]
+fn xor_on_known_genomes() {
+    let hyper = HyperSettings::default();
+
+    assert!(Xor::default().solved(&xor_solver(), &hyper));
+    assert!(Xor::default().evaluate(&xor_solver(), &hyper) > 0.95);
+
+    // Every output exactly on the boundary counts as wrong
+    assert!(!Xor::default().solved(&silent(2, 1), &hyper));
+    assert_eq!(Xor::default().evaluate(&silent(2, 1), &hyper), 0.25);
+}
+
+#[test]
+fn parity_cases() {
+    let cases = Parity::new(3).unwrap().cases();
+
+    assert_eq!(cases.len(), 8);
+    assert_eq!(cases.iter().filter(|(_, target)| *target == 1.).count(), 4);
+    assert!(cases
+        .iter()
+        .all(|(inputs, target)| inputs.iter().sum::<f64>() % 2. == *target));
+
+    assert!(!Parity::new(3)
+        .unwrap()
+        .solved(&silent(3, 1), &HyperSettings::default()));
+}
+
+#[test]
+fn parity_needs_1_to_16_bits() {
+    assert_eq!(Parity::new(1).unwrap().cases().len(), 2);
+    assert_eq!(Parity::new(16).unwrap().bits(), 16);
+
+    for &bits in &[0, 17, 64] {
+        assert!(matches!(
+            Parity::new(bits),
+            Err(HyperDarwinError::InvalidParityBits(b)) if b == bits
+        ));
+        assert!(tasks::build("parity", &serde_json::json!({ "bits": bits })).is_err());
+    }
+
+    let task = tasks::build("parity", &Value::Null).unwrap();
+    assert_eq!(
+        task.encoding(),
+        Encoding::Direct {
+            inputs: 3,
+            outputs: 1
+        }
+    );
+}
+
+#[test]
+fn retina_objects() {
+    let left = (0..16u8).filter(|&p| Retina::is_left_object(p)).count();
+    let right = (0..16u8).filter(|&p| Retina::is_right_object(p)).count();
+
+    assert_eq!((left, right), (8, 8));
+
+    let cases = Retina::default().cases();
+
+    assert_eq!(cases.len(), 256);
+    assert_eq!(cases.iter().filter(|(_, target)| *target == 1.).count(), 64);
+
+    let hyper = HyperSettings::default();
+    assert!(!Retina::default().solved(&silent(4, 1), &hyper));
+}
+
+// No built-in task counts a Genome that outputs nothing as a solution, and
+// every fitness stays within [0, 1]
+#[test]
+fn silent_genomes_solve_nothing() {
+    let hyper = HyperSettings::default();
+
+    for &name in tasks::TASKS {
+        let task = tasks::build(name, &Value::Null).unwrap();
+
+        let genome = match task.encoding() {
+            Encoding::Cppn => silent(4, 1),
+            Encoding::Direct { inputs, outputs } => silent(inputs, outputs),
+        };
+
+        let fitness = task.evaluate(&genome, &hyper);
+
+        assert!((0. ..=1.).contains(&fitness), "{}: {}", name, fitness);
+        assert!(!task.solved(&genome, &hyper), "{}", name);
+    }
+}
+
+fn solve(
+    task: &str,
+    task_params: Value,
+    settings: Settings,
+    seed: u64,
+    generations: u64,
+) -> Option<u64> {
+    let config = ExperimentConfig {
+        task: String::from(task),
+        task_params,
+        seed: Some(seed),
+        generations,
+        output_dir: env::temp_dir().join(format!("hyper_darwin_solve_{}", task)),
+        checkpoint_interval: 0,
+        settings,
+        ..ExperimentConfig::default()
+    };
+
+    let mut experiment = Experiment::new(config).unwrap();
+    let mut solved_at = None;
+
+    experiment
+        .run(|stats| {
+            if stats.solved && solved_at.is_none() {
+                solved_at = Some(stats.generation);
+            }
+        })
+        .unwrap();
+
+    solved_at
+}
+
+#[test]
+fn xor_gets_solved() {
+    let settings = Settings::new(150).speciation_threshold(1.);
+
+    assert!(solve("xor", Value::Null, settings, 1, 200).is_some());
+}
+
+#[test]
+fn parity_gets_solved() {
+    let settings = Settings::new(150)
+        .activations(ActivationSet::Single(Activations::Sigmoid))
+        .node_mut_rate(0.1)
+        .conn_mut_rate(0.2)
+        .speciation(Speciation::KMedoids(12))
+        .allowed_stagnancy(30);
+
+    let bits = serde_json::json!({ "bits": 3 });
+
+    assert!(solve("parity", bits, settings, 24, 400).is_some());
+}